@@ -28,4 +28,139 @@ pub enum ErrorCode {
 
     #[msg("NFT vault is empty - cannot complete offer")]
     EmptyVault,
+
+    #[msg("NFT is not a verified member of the bid's collection")]
+    CollectionMismatch,
+
+    #[msg("Failed to refund escrowed bid tokens")]
+    FailedBidRefund,
+
+    #[msg("Offer expiry must be in the future")]
+    InvalidExpiry,
+
+    #[msg("Offer has expired")]
+    OfferExpired,
+
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+
+    #[msg("Fee basis points must not exceed 1000")]
+    InvalidFeeBps,
+
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("NFT metadata account is invalid")]
+    InvalidMetadata,
+
+    #[msg("Missing payment account for a royalty creator")]
+    MissingCreatorAccount,
+
+    #[msg("Creator payment account does not match the creator's ATA")]
+    InvalidCreatorAccount,
+
+    #[msg("Failed to transfer royalty to creator")]
+    FailedRoyaltyTransfer,
+
+    #[msg("Offer price does not match (or exceeds) the price the taker expected")]
+    PriceMismatch,
+
+    #[msg("Offer payment mint does not match the mint the taker expected")]
+    PaymentMintMismatch,
+
+    #[msg("Offer is private to a different taker")]
+    TakerNotAllowed,
+
+    #[msg("Payment token accounts are required for token-priced offers")]
+    MissingPaymentAccount,
+
+    #[msg("Dutch auction needs a start price at or above a non-zero end price and a positive duration")]
+    InvalidDutchAuction,
+
+    #[msg("Dutch auction listings cannot be repriced")]
+    DutchAuctionNotEditable,
+
+    #[msg("Auction needs an end time within 30 days, an extension window of at most 1 hour and a non-zero bid increment")]
+    InvalidAuction,
+
+    #[msg("Auction has ended")]
+    AuctionEnded,
+
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+
+    #[msg("Bid is below the minimum next bid")]
+    BidTooLow,
+
+    #[msg("Seller cannot bid on their own auction")]
+    SellerCannotBid,
+
+    #[msg("Auction already has bids")]
+    AuctionHasBids,
+
+    #[msg("Highest bidder accounts are missing or do not match the auction")]
+    BidderMismatch,
+
+    #[msg("NFT does not match what the swap offer requests")]
+    RequestedNftMismatch,
+
+    #[msg("Swap top-up needs a non-zero amount and a payment mint")]
+    InvalidSwapTopUp,
+
+    #[msg("Bundle accounts are missing, out of order or exceed the maximum bundle size")]
+    InvalidBundleAccounts,
+
+    #[msg("Batch accounts are missing or do not match the offers")]
+    InvalidBatchAccounts,
+
+    #[msg("Offer price would exceed the batch spend cap")]
+    SpendCapExceeded,
+
+    #[msg("NFT vault or maker NFT account does not match the listing's custody mode")]
+    InvalidCustodyAccounts,
+
+    #[msg("Maker no longer holds the NFT or revoked the listing delegate")]
+    DelegateRevoked,
+
+    #[msg("Programmable NFT transfers need the metadata, token record and instructions sysvar accounts")]
+    MissingPnftAccount,
+
+    #[msg("Account is not an MPL Core asset or collection")]
+    InvalidCoreAsset,
+
+    #[msg("NFT mint must have zero decimals")]
+    InvalidNftDecimals,
+
+    #[msg("NFT mint must have a supply of exactly one")]
+    InvalidNftSupply,
+
+    #[msg("NFT mint authority must be empty or the mint's master edition")]
+    InvalidNftMintAuthority,
+
+    #[msg("NFT mints with a permanent delegate cannot be listed")]
+    PermanentDelegateMint,
+
+    #[msg("Non-transferable NFT mints cannot be listed")]
+    NonTransferableMint,
+
+    #[msg("NFT mints with a transfer hook cannot be listed")]
+    TransferHookMint,
+
+    #[msg("NFT mints with a transfer fee cannot be listed")]
+    TransferFeeMint,
+
+    #[msg("Payment mints with a transfer fee cannot be used for auction bids")]
+    TransferFeePaymentMint,
+
+    #[msg("NFT account is frozen and cannot be transferred in a batch")]
+    FrozenNftAccount,
+
+    #[msg("Offer has already been taken or cancelled")]
+    OfferClosed,
+
+    #[msg("Offer payment mode (SPL token or native SOL) does not match the expected mode")]
+    PaymentModeMismatch,
 }
\ No newline at end of file
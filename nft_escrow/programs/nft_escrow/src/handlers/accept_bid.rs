@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{Metadata, MetadataAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    close_payment_vault, creator_royalties, require_strict_nft, require_verified_collection,
+    settle_sale, transfer_tokens, SalePayment, TokenPayment,
+};
+use crate::{
+    error::ErrorCode,
+    state::{CollectionBid, MarketplaceConfig},
+};
+
+#[derive(Accounts)]
+pub struct AcceptBid<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// Metaplex Token Metadata program (owns the NFT metadata)
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The NFT holder filling the bid (sends NFT, receives payment)
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The user who placed the bid (receives the NFT)
+    /// CHECK: Validated via has_one constraint on bid account
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// The NFT mint being sold into the bid
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Metadata of the NFT (proves collection membership)
+    #[account(
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub nft_metadata: Account<'info, MetadataAccount>,
+
+    /// The payment token mint
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Seller's NFT account (must have exactly 1 NFT)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Buyer's NFT account (receives the NFT)
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub buyer_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seller's payment token account (receives payment)
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub seller_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee
+    /// CHECK: Validated via has_one constraint on config account
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives marketplace fee)
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub treasury_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The bid account (will be closed)
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer,
+        has_one = payment_mint,
+        seeds = [
+            b"collection_bid",
+            buyer.key().as_ref(),
+            bid.bid_id.to_le_bytes().as_ref(),
+        ],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, CollectionBid>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bid_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Fill a collection bid by selling an NFT from that collection
+/// The bid is split between the marketplace fee, creator royalties and seller.
+/// Remaining accounts: payment token account of each verified royalty creator,
+/// in the order they appear in the NFT metadata
+pub fn accept_bid<'info>(ctx: Context<'_, '_, 'info, 'info, AcceptBid<'info>>) -> Result<()> {
+    // Validate the NFT is a real single-edition mint
+    require_strict_nft(&ctx.accounts.nft_mint)?;
+
+    // Validate seller has exactly 1 NFT (standard NFT has amount = 1)
+    require!(
+        ctx.accounts.seller_nft_account.amount == 1,
+        ErrorCode::FailedNftTransfer
+    );
+
+    // Validate NFT belongs to the bid's verified collection
+    require_verified_collection(
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.bid.collection_mint,
+    )?;
+
+    // Prepare PDA signer seeds for vault operations
+    let bid_seeds = &[
+        b"collection_bid",
+        ctx.accounts.bid.buyer.as_ref(),
+        &ctx.accounts.bid.bid_id.to_le_bytes()[..],
+        &[ctx.accounts.bid.bump],
+    ];
+    let signer_seeds = &[&bid_seeds[..]];
+
+    // Step 1: Transfer NFT from seller to buyer
+    transfer_tokens(
+        &ctx.accounts.seller_nft_account,
+        &ctx.accounts.buyer_nft_account,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        None, // Seller is regular signer
+    )
+    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+    // Step 2: Pay marketplace fee, creator royalties and the seller from the bid vault
+    let price = ctx.accounts.bid.token_amount;
+    let fee_amount = ctx.accounts.config.fee_for(price)?;
+    let royalties = creator_royalties(&ctx.accounts.nft_metadata.to_account_info(), price)?;
+    let payment = SalePayment {
+        payer: ctx.accounts.bid.to_account_info(),
+        maker: ctx.accounts.seller.to_account_info(),
+        treasury: ctx.accounts.treasury.to_account_info(),
+        system_program: &ctx.accounts.system_program,
+        token: Some(TokenPayment {
+            mint: &ctx.accounts.payment_mint,
+            payer_account: &ctx.accounts.bid_vault,
+            maker_account: &ctx.accounts.seller_payment_account,
+            treasury_account: &ctx.accounts.treasury_payment_account,
+            token_program: &ctx.accounts.payment_token_program,
+        }),
+        signer_seeds: Some(signer_seeds),
+    };
+    let (seller_amount, royalty_amount) = settle_sale(
+        &payment,
+        price,
+        fee_amount,
+        &royalties,
+        ctx.remaining_accounts,
+    )?;
+
+    // Step 3: Close the payment vault (return rent to buyer; stray tokens keep it open)
+    close_payment_vault(
+        &ctx.accounts.bid_vault,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.bid.to_account_info(),
+        &ctx.accounts.payment_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultClosure)?;
+
+    msg!(
+        "Bid accepted: NFT transferred to buyer, {} paid to seller, {} fee, {} royalties",
+        seller_amount,
+        fee_amount,
+        royalty_amount
+    );
+
+    // Bid account automatically closes (close = buyer constraint)
+    Ok(())
+}
\ No newline at end of file
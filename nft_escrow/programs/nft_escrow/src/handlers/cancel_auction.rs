@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{close_payment_vault, close_token_account, transfer_tokens};
+use crate::{error::ErrorCode, state::Auction};
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for account management
+    pub system_program: Program<'info, System>,
+
+    /// The auction creator (only they can cancel)
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The NFT mint
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// The token mint bids are paid in
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Seller's NFT account (receives refunded NFT)
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The auction account (will be closed)
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        has_one = nft_mint,
+        has_one = payment_mint,
+        seeds = [
+            b"auction",
+            seller.key().as_ref(),
+            auction.auction_id.to_le_bytes().as_ref(),
+        ],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Vault holding the escrowed NFT
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault for bids (empty while there are no bids)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Cancel an auction that has no bids and return the NFT to the seller
+pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+    // Validate nobody has bid yet
+    require!(
+        ctx.accounts.auction.highest_bidder.is_none(),
+        ErrorCode::AuctionHasBids
+    );
+
+    // Validate vault has the NFT
+    require!(
+        ctx.accounts.nft_vault.amount == 1,
+        ErrorCode::EmptyVault
+    );
+
+    // Prepare PDA signer seeds
+    let auction_seeds = &[
+        b"auction",
+        ctx.accounts.auction.seller.as_ref(),
+        &ctx.accounts.auction.auction_id.to_le_bytes()[..],
+        &[ctx.accounts.auction.bump],
+    ];
+    let signer_seeds = &[&auction_seeds[..]];
+
+    // Step 1: Return NFT from vault to seller
+    transfer_tokens(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.seller_nft_account,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.auction.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedNftReturn)?;
+
+    // Step 2: Close both vaults (return rent to seller; stray payment tokens keep theirs open)
+    close_token_account(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.auction.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedCancelClosure)?;
+    close_payment_vault(
+        &ctx.accounts.payment_vault,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.auction.to_account_info(),
+        &ctx.accounts.payment_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedCancelClosure)?;
+
+    msg!("Auction cancelled: NFT returned to seller");
+
+    // Auction account automatically closes (close = seller constraint)
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::shared::{close_payment_vault, transfer_tokens};
+use crate::{error::ErrorCode, state::CollectionBid};
+
+#[derive(Accounts)]
+pub struct CancelBid<'info> {
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program for account management
+    pub system_program: Program<'info, System>,
+
+    /// The original bidder (only they can cancel)
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The payment token mint
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Buyer's payment token account (receives refunded payment)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The bid account (will be closed)
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer,
+        has_one = payment_mint,
+        seeds = [
+            b"collection_bid",
+            buyer.key().as_ref(),
+            bid.bid_id.to_le_bytes().as_ref(),
+        ],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, CollectionBid>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = token_program,
+    )]
+    pub bid_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Cancel a collection bid and refund the escrowed payment to the buyer
+pub fn cancel_bid(ctx: Context<CancelBid>) -> Result<()> {
+    // Prepare PDA signer seeds
+    let bid_seeds = &[
+        b"collection_bid",
+        ctx.accounts.bid.buyer.as_ref(),
+        &ctx.accounts.bid.bid_id.to_le_bytes()[..],
+        &[ctx.accounts.bid.bump],
+    ];
+    let signer_seeds = &[&bid_seeds[..]];
+
+    // Step 1: Refund escrowed payment from vault to buyer
+    transfer_tokens(
+        &ctx.accounts.bid_vault,
+        &ctx.accounts.buyer_payment_account,
+        ctx.accounts.bid_vault.amount,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.bid.to_account_info(),
+        &ctx.accounts.token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedBidRefund)?;
+
+    // Step 2: Close the payment vault (return rent to buyer; stray tokens keep it open)
+    close_payment_vault(
+        &ctx.accounts.bid_vault,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.bid.to_account_info(),
+        &ctx.accounts.token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedCancelClosure)?;
+
+    msg!("Bid cancelled: payment refunded to buyer");
+
+    // Bid account automatically closes (close = buyer constraint)
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use super::core_asset::{require_core_collection, transfer_core_asset, CoreTransfer, MPL_CORE_ID};
+use crate::{error::ErrorCode, state::CoreOffer};
+
+#[derive(Accounts)]
+pub struct CancelCoreOffer<'info> {
+    /// Metaplex Core program (owns the asset)
+    /// CHECK: Address checked against the Core program ID
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    /// System program for account management
+    pub system_program: Program<'info, System>,
+
+    /// The original offer creator (only they can cancel)
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The MPL Core asset being returned
+    /// CHECK: Validated via has_one constraint on offer account
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// Core collection of the asset (omit if the asset is not in a collection)
+    /// CHECK: Must match the collection saved on the offer; validated in the handler
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    /// The offer account holding the asset (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = asset,
+        seeds = [
+            b"core_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, CoreOffer>,
+}
+
+/// Cancel an MPL Core offer and return the asset to the maker
+pub fn cancel_core_offer(ctx: Context<CancelCoreOffer>) -> Result<()> {
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+    require_core_collection(collection.as_ref(), ctx.accounts.offer.collection)?;
+
+    // Prepare PDA signer seeds
+    let offer_seeds = &[
+        b"core_offer",
+        ctx.accounts.offer.maker.as_ref(),
+        &ctx.accounts.offer.offer_id.to_le_bytes()[..],
+        &[ctx.accounts.offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Return the asset from the offer PDA to maker
+    transfer_core_asset(
+        CoreTransfer {
+            asset: ctx.accounts.asset.to_account_info(),
+            collection,
+            payer: ctx.accounts.maker.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+            new_owner: ctx.accounts.maker.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            core_program: ctx.accounts.mpl_core_program.to_account_info(),
+        },
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedNftReturn)?;
+
+    msg!("Core offer cancelled: asset returned to maker");
+
+    // Offer account automatically closes (close = maker constraint)
+    Ok(())
+}
\ No newline at end of file
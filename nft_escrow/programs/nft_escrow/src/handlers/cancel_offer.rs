@@ -1,12 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
 
-use super::shared::{close_token_account, transfer_tokens};
-use crate::{error::ErrorCode, state::NftOffer};
+use super::pnft::*;
+use super::shared::{close_token_account, revoke_delegate, BundleTransfer};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, NftOffer},
+};
 
 #[derive(Accounts)]
 pub struct CancelOffer<'info> {
-    /// Token program (SPL Token or Token-2022)
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
     pub token_program: Interface<'info, TokenInterface>,
 
     /// System program for account management
@@ -22,9 +33,11 @@ pub struct CancelOffer<'info> {
     )]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    /// Maker's NFT account (receives refunded NFT)
+    /// Maker's NFT account (receives refunded NFT, or has its listing delegate revoked;
+    /// recreated if a pNFT transfer closed it)
     #[account(
-        mut,
+        init_if_needed,
+        payer = maker,
         associated_token::mint = nft_mint,
         associated_token::authority = maker,
         associated_token::token_program = token_program,
@@ -37,52 +50,87 @@ pub struct CancelOffer<'info> {
         close = maker,
         has_one = maker,
         has_one = nft_mint,
-        seeds = [b"nft_offer", offer.offer_id.to_le_bytes().as_ref()],
+        seeds = [
+            b"nft_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
         bump = offer.bump
     )]
     pub offer: Account<'info, NftOffer>,
 
-    /// Vault holding the escrowed NFT
+    /// Vault holding the escrowed NFT (omit for delegate listings)
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = offer,
         associated_token::token_program = token_program,
     )]
-    pub nft_vault: InterfaceAccount<'info, TokenAccount>,
+    pub nft_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token Metadata accounts of a programmable NFT (omit for regular NFTs)
+    pub pnft: PnftAccounts<'info>,
 }
 
 /// Cancel an NFT offer and return the NFT to the maker
-pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+/// Delegate listings only revoke the offer's approval on the maker's account.
+/// Remaining accounts (bundle listings only): nft_mint, nft_vault and
+/// maker_nft_account of each extra NFT in listing order
+pub fn cancel_offer<'info>(ctx: Context<'_, '_, 'info, 'info, CancelOffer<'info>>) -> Result<()> {
+    // Delegate listings: the NFT never left the maker's wallet
+    if ctx.accounts.offer.custody == CustodyMode::Delegate {
+        // Leave any delegate the maker approved since listing untouched
+        if ctx.accounts.maker_nft_account.delegate == COption::Some(ctx.accounts.offer.key()) {
+            revoke_delegate(
+                &ctx.accounts.maker_nft_account,
+                &ctx.accounts.maker.to_account_info(),
+                &ctx.accounts.token_program,
+            )
+            .map_err(|_| ErrorCode::FailedCancelClosure)?;
+        }
+
+        msg!("Offer cancelled: listing delegate revoked");
+
+        // Offer account automatically closes (close = maker constraint)
+        return Ok(());
+    }
+
     // Validate vault has the NFT
-    require!(
-        ctx.accounts.nft_vault.amount == 1,
-        ErrorCode::EmptyVault
-    );
+    let Some(nft_vault) = ctx.accounts.nft_vault.as_ref() else {
+        return err!(ErrorCode::InvalidCustodyAccounts);
+    };
+    require!(nft_vault.amount == 1, ErrorCode::EmptyVault);
 
     // Prepare PDA signer seeds
     let offer_seeds = &[
         b"nft_offer",
+        ctx.accounts.offer.maker.as_ref(),
         &ctx.accounts.offer.offer_id.to_le_bytes()[..],
         &[ctx.accounts.offer.bump],
     ];
     let signer_seeds = &[&offer_seeds[..]];
 
     // Step 1: Return NFT from vault to maker
-    transfer_tokens(
-        &ctx.accounts.nft_vault,
-        &ctx.accounts.maker_nft_account,
-        1, // NFTs have amount = 1
-        &ctx.accounts.nft_mint,
-        &ctx.accounts.offer.to_account_info(),
-        &ctx.accounts.token_program,
+    transfer_nft(
+        NftTransfer {
+            from: nft_vault,
+            from_owner: ctx.accounts.offer.to_account_info(),
+            to: &ctx.accounts.maker_nft_account,
+            to_owner: ctx.accounts.maker.to_account_info(),
+            mint: &ctx.accounts.nft_mint,
+            payer: ctx.accounts.maker.to_account_info(),
+            token_program: &ctx.accounts.token_program,
+            associated_token_program: &ctx.accounts.associated_token_program,
+            system_program: &ctx.accounts.system_program,
+            pnft: &ctx.accounts.pnft,
+        },
         Some(signer_seeds),
     )
     .map_err(|_| ErrorCode::FailedNftReturn)?;
 
     // Step 2: Close the NFT vault (return rent to maker)
     close_token_account(
-        &ctx.accounts.nft_vault,
+        nft_vault,
         &ctx.accounts.maker.to_account_info(),
         &ctx.accounts.offer.to_account_info(),
         &ctx.accounts.token_program,
@@ -90,7 +138,23 @@ pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
     )
     .map_err(|_| ErrorCode::FailedCancelClosure)?;
 
-    msg!("Offer cancelled: NFT returned to maker");
+    // Step 3: Return the extra NFTs of a bundle listing
+    let bundle = BundleTransfer {
+        offer: ctx.accounts.offer.to_account_info(),
+        payer: ctx.accounts.maker.to_account_info(),
+        token_program: &ctx.accounts.token_program,
+        associated_token_program: &ctx.accounts.associated_token_program,
+        system_program: &ctx.accounts.system_program,
+    };
+    bundle.release(
+        ctx.remaining_accounts,
+        &ctx.accounts.offer.bundle_mints,
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        signer_seeds,
+    )?;
+
+    msg!("Offer cancelled: NFTs returned to maker");
 
     // Offer account automatically closes (close = maker constraint)
     Ok(())
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use super::shared::{close_payment_vault, close_token_account, transfer_tokens};
+use crate::{error::ErrorCode, state::SwapOffer};
+
+#[derive(Accounts)]
+pub struct CancelSwapOffer<'info> {
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for account management
+    pub system_program: Program<'info, System>,
+
+    /// The original offer creator (only they can cancel)
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The NFT mint
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// The token mint of the top-up (omit for swaps without a maker-paid top-up)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Maker's NFT account (receives refunded NFT)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub maker_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Maker's payment token account (receives refunded top-up)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The swap offer account (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = nft_mint,
+        seeds = [
+            b"swap_offer",
+            maker.key().as_ref(),
+            swap_offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = swap_offer.bump
+    )]
+    pub swap_offer: Account<'info, SwapOffer>,
+
+    /// Vault holding the escrowed NFT
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault holding a maker-paid top-up
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub top_up_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+/// Cancel a swap offer and return the NFT (and any escrowed top-up) to the maker
+pub fn cancel_swap_offer(ctx: Context<CancelSwapOffer>) -> Result<()> {
+    // Validate vault has the NFT
+    require!(
+        ctx.accounts.nft_vault.amount == 1,
+        ErrorCode::EmptyVault
+    );
+
+    // Prepare PDA signer seeds
+    let offer_seeds = &[
+        b"swap_offer",
+        ctx.accounts.swap_offer.maker.as_ref(),
+        &ctx.accounts.swap_offer.offer_id.to_le_bytes()[..],
+        &[ctx.accounts.swap_offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Step 1: Return NFT from vault to maker
+    transfer_tokens(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.maker_nft_account,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.swap_offer.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedNftReturn)?;
+
+    // Step 2: Close the NFT vault (return rent to maker)
+    close_token_account(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.swap_offer.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedCancelClosure)?;
+
+    // Step 3: Refund an escrowed top-up
+    let escrowed_top_up = ctx.accounts.swap_offer.escrowed_top_up();
+    if escrowed_top_up > 0 {
+        let (Some(mint), Some(maker_payment_account), Some(top_up_vault)) = (
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.maker_payment_account.as_deref(),
+            ctx.accounts.top_up_vault.as_deref(),
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccount);
+        };
+        require!(
+            ctx.accounts.swap_offer.payment_mint == Some(mint.key()),
+            ErrorCode::PaymentMintMismatch
+        );
+        transfer_tokens(
+            top_up_vault,
+            maker_payment_account,
+            escrowed_top_up,
+            mint,
+            &ctx.accounts.swap_offer.to_account_info(),
+            &ctx.accounts.payment_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedPaymentTransfer)?;
+        close_payment_vault(
+            top_up_vault,
+            &ctx.accounts.maker.to_account_info(),
+            &ctx.accounts.swap_offer.to_account_info(),
+            &ctx.accounts.payment_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedCancelClosure)?;
+    }
+
+    msg!("Swap offer cancelled: NFT returned to maker");
+
+    // Swap offer account automatically closes (close = maker constraint)
+    Ok(())
+}
\ No newline at end of file
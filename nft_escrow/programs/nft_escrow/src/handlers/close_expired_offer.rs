@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::pnft::*;
+use super::shared::{close_token_account, BundleTransfer};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, NftOffer},
+};
+
+#[derive(Accounts)]
+pub struct CloseExpiredOffer<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// Anyone cleaning up the expired offer (pays for maker's NFT account if missing)
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// The original offer creator (receives NFT and rent)
+    /// CHECK: Validated via has_one constraint on offer account
+    #[account(mut)]
+    pub maker: UncheckedAccount<'info>,
+
+    /// The NFT mint
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Maker's NFT account (receives returned NFT)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The offer account (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = nft_mint,
+        seeds = [
+            b"nft_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, NftOffer>,
+
+    /// Vault holding the escrowed NFT (omit for delegate listings)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub nft_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token Metadata accounts of a programmable NFT (omit for regular NFTs)
+    pub pnft: PnftAccounts<'info>,
+}
+
+/// Close an expired NFT offer and return the NFT to the maker
+/// Delegate listings only close the offer: revoking the approval needs the
+/// maker's signature, but the closed offer PDA can never sign again.
+/// Remaining accounts (bundle listings only): nft_mint, nft_vault and
+/// maker_nft_account of each extra NFT in listing order
+pub fn close_expired_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseExpiredOffer<'info>>,
+) -> Result<()> {
+    // Validate offer has expired
+    require!(
+        ctx.accounts.offer.is_expired(Clock::get()?.unix_timestamp),
+        ErrorCode::OfferNotExpired
+    );
+
+    // Delegate listings: the NFT never left the maker's wallet
+    if ctx.accounts.offer.custody == CustodyMode::Delegate {
+        msg!("Expired delegate offer closed");
+
+        // Offer account automatically closes (close = maker constraint)
+        return Ok(());
+    }
+
+    // Validate vault has the NFT
+    let Some(nft_vault) = ctx.accounts.nft_vault.as_ref() else {
+        return err!(ErrorCode::InvalidCustodyAccounts);
+    };
+    require!(nft_vault.amount == 1, ErrorCode::EmptyVault);
+
+    // Prepare PDA signer seeds
+    let offer_seeds = &[
+        b"nft_offer",
+        ctx.accounts.offer.maker.as_ref(),
+        &ctx.accounts.offer.offer_id.to_le_bytes()[..],
+        &[ctx.accounts.offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Step 1: Return NFT from vault to maker
+    transfer_nft(
+        NftTransfer {
+            from: nft_vault,
+            from_owner: ctx.accounts.offer.to_account_info(),
+            to: &ctx.accounts.maker_nft_account,
+            to_owner: ctx.accounts.maker.to_account_info(),
+            mint: &ctx.accounts.nft_mint,
+            payer: ctx.accounts.caller.to_account_info(),
+            token_program: &ctx.accounts.token_program,
+            associated_token_program: &ctx.accounts.associated_token_program,
+            system_program: &ctx.accounts.system_program,
+            pnft: &ctx.accounts.pnft,
+        },
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedNftReturn)?;
+
+    // Step 2: Close the NFT vault (return rent to maker)
+    close_token_account(
+        nft_vault,
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.offer.to_account_info(),
+        &ctx.accounts.token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedCancelClosure)?;
+
+    // Step 3: Return the extra NFTs of a bundle listing (caller pays for missing accounts)
+    let bundle = BundleTransfer {
+        offer: ctx.accounts.offer.to_account_info(),
+        payer: ctx.accounts.caller.to_account_info(),
+        token_program: &ctx.accounts.token_program,
+        associated_token_program: &ctx.accounts.associated_token_program,
+        system_program: &ctx.accounts.system_program,
+    };
+    bundle.release(
+        ctx.remaining_accounts,
+        &ctx.accounts.offer.bundle_mints,
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+        signer_seeds,
+    )?;
+
+    msg!("Expired offer closed: NFTs returned to maker");
+
+    // Offer account automatically closes (close = maker constraint)
+    Ok(())
+}
\ No newline at end of file
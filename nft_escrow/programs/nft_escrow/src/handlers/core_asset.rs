@@ -0,0 +1,261 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+
+use crate::error::ErrorCode;
+
+/// Metaplex Core program
+pub const MPL_CORE_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+/// Core account key of an asset
+const ASSET_V1_KEY: u8 = 1;
+
+/// Core account key of a plugin header
+const PLUGIN_HEADER_V1_KEY: u8 = 3;
+
+/// Core account key of a collection
+const COLLECTION_V1_KEY: u8 = 5;
+
+/// Core plugin type of the Royalties plugin
+const ROYALTIES_PLUGIN: u8 = 0;
+
+/// Core instruction discriminator of TransferV1
+const TRANSFER_V1_DISCRIMINATOR: u8 = 14;
+
+/// Update authority of a Core asset
+#[derive(AnchorDeserialize)]
+enum UpdateAuthority {
+    None,
+    Address { _address: Pubkey },
+    Collection(Pubkey),
+}
+
+/// Base of a Core asset account (the plugin header, if any, follows it)
+#[derive(AnchorDeserialize)]
+struct BaseAssetV1 {
+    key: u8,
+    owner: Pubkey,
+    update_authority: UpdateAuthority,
+    _name: String,
+    _uri: String,
+    _seq: Option<u64>,
+}
+
+/// Base of a Core collection account (the plugin header, if any, follows it)
+#[derive(AnchorDeserialize)]
+struct BaseCollectionV1 {
+    key: u8,
+    _update_authority: Pubkey,
+    _name: String,
+    _uri: String,
+    _num_minted: u32,
+    _current_size: u32,
+}
+
+#[derive(AnchorDeserialize)]
+struct PluginHeaderV1 {
+    key: u8,
+    plugin_registry_offset: u64,
+}
+
+#[derive(AnchorDeserialize)]
+enum PluginAuthority {
+    None,
+    Owner,
+    UpdateAuthority,
+    Address { _address: Pubkey },
+}
+
+#[derive(AnchorDeserialize)]
+struct RegistryRecord {
+    plugin_type: u8,
+    _authority: PluginAuthority,
+    offset: u64,
+}
+
+#[derive(AnchorDeserialize)]
+struct PluginRegistryV1 {
+    _key: u8,
+    registry: Vec<RegistryRecord>,
+}
+
+#[derive(AnchorDeserialize)]
+struct CoreCreator {
+    address: Pubkey,
+    percentage: u8,
+}
+
+/// Royalties plugin data (the trailing rule set is not needed)
+#[derive(AnchorDeserialize)]
+struct RoyaltiesPlugin {
+    _plugin_type: u8,
+    basis_points: u16,
+    creators: Vec<CoreCreator>,
+}
+
+/// Owner and collection of an MPL Core asset
+pub struct CoreAsset {
+    pub owner: Pubkey,
+    /// Collection the asset belongs to (None = not in a collection)
+    pub collection: Option<Pubkey>,
+}
+
+/// Read an MPL Core asset account, checking Core owns it
+pub fn read_core_asset(asset: &AccountInfo) -> Result<CoreAsset> {
+    require_keys_eq!(*asset.owner, MPL_CORE_ID, ErrorCode::InvalidCoreAsset);
+    let data = asset.try_borrow_data()?;
+    let base = BaseAssetV1::deserialize(&mut &data[..]).map_err(|_| ErrorCode::InvalidCoreAsset)?;
+    require!(base.key == ASSET_V1_KEY, ErrorCode::InvalidCoreAsset);
+
+    let collection = match base.update_authority {
+        UpdateAuthority::Collection(collection) => Some(collection),
+        UpdateAuthority::None | UpdateAuthority::Address { .. } => None,
+    };
+    Ok(CoreAsset {
+        owner: base.owner,
+        collection,
+    })
+}
+
+/// Check the collection account passed in is the asset's Core collection
+/// (Core needs it to move assets that belong to a collection)
+pub fn require_core_collection(
+    collection: Option<&AccountInfo>,
+    expected: Option<Pubkey>,
+) -> Result<()> {
+    require!(
+        collection.map(|collection| collection.key()) == expected,
+        ErrorCode::CollectionMismatch
+    );
+    Ok(())
+}
+
+/// Royalty owed to each creator of a Core asset on a sale of `sale_amount`
+/// Uses the asset's Royalties plugin, falling back to its collection's;
+/// returns an empty list when neither has one. Creators whose share rounds
+/// down to zero are left out
+pub fn core_royalties(
+    asset: &AccountInfo,
+    collection: Option<&AccountInfo>,
+    sale_amount: u64,
+) -> Result<Vec<(Pubkey, u64)>> {
+    let royalties = match royalties_plugin(asset, ASSET_V1_KEY)? {
+        Some(royalties) => Some(royalties),
+        None => match collection {
+            Some(collection) => royalties_plugin(collection, COLLECTION_V1_KEY)?,
+            None => None,
+        },
+    };
+    let Some(royalties) = royalties else {
+        return Ok(Vec::new());
+    };
+
+    // Total royalty pool, then each creator's percentage share of it
+    let royalty_pool = (sale_amount as u128)
+        .checked_mul(royalties.basis_points as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / 10_000;
+
+    let royalties = royalties
+        .creators
+        .iter()
+        .map(|creator| (creator.address, (royalty_pool * creator.percentage as u128 / 100) as u64))
+        .filter(|(_, amount)| *amount > 0)
+        .collect();
+
+    Ok(royalties)
+}
+
+/// Find the Royalties plugin of a Core asset or collection account
+fn royalties_plugin(account: &AccountInfo, key: u8) -> Result<Option<RoyaltiesPlugin>> {
+    require_keys_eq!(*account.owner, MPL_CORE_ID, ErrorCode::InvalidCoreAsset);
+    let data = account.try_borrow_data()?;
+
+    // Skip the base account; the plugin header (if any) comes right after it
+    let mut cursor = &data[..];
+    let base_key = match key {
+        ASSET_V1_KEY => BaseAssetV1::deserialize(&mut cursor).map(|base| base.key),
+        _ => BaseCollectionV1::deserialize(&mut cursor).map(|base| base.key),
+    }
+    .map_err(|_| ErrorCode::InvalidCoreAsset)?;
+    require!(base_key == key, ErrorCode::InvalidCoreAsset);
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+
+    // Look the plugin up in the registry, then read it at its offset
+    let header =
+        PluginHeaderV1::deserialize(&mut cursor).map_err(|_| ErrorCode::InvalidCoreAsset)?;
+    require!(header.key == PLUGIN_HEADER_V1_KEY, ErrorCode::InvalidCoreAsset);
+    let registry = data
+        .get(header.plugin_registry_offset as usize..)
+        .and_then(|mut bytes| PluginRegistryV1::deserialize(&mut bytes).ok())
+        .ok_or(ErrorCode::InvalidCoreAsset)?;
+    let Some(record) = registry
+        .registry
+        .iter()
+        .find(|record| record.plugin_type == ROYALTIES_PLUGIN)
+    else {
+        return Ok(None);
+    };
+    let royalties = data
+        .get(record.offset as usize..)
+        .and_then(|mut bytes| RoyaltiesPlugin::deserialize(&mut bytes).ok())
+        .ok_or(ErrorCode::InvalidCoreAsset)?;
+
+    Ok(Some(royalties))
+}
+
+/// Accounts used to move an MPL Core asset with Core's TransferV1 instruction
+pub struct CoreTransfer<'info> {
+    pub asset: AccountInfo<'info>,
+    /// Collection of the asset (None = not in a collection)
+    pub collection: Option<AccountInfo<'info>>,
+    pub payer: AccountInfo<'info>,
+    /// Current owner of the asset
+    pub authority: AccountInfo<'info>,
+    pub new_owner: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub core_program: AccountInfo<'info>,
+}
+
+/// Transfer an MPL Core asset to a new owner
+/// Supports both regular signers and PDA signers as the owner
+pub fn transfer_core_asset(
+    transfer: CoreTransfer,
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<()> {
+    // Core takes its own program ID in place of omitted optional accounts
+    let collection = transfer
+        .collection
+        .unwrap_or_else(|| transfer.core_program.clone());
+
+    let instruction = Instruction {
+        program_id: MPL_CORE_ID,
+        accounts: vec![
+            AccountMeta::new(transfer.asset.key(), false),
+            AccountMeta::new_readonly(collection.key(), false),
+            AccountMeta::new(transfer.payer.key(), true),
+            AccountMeta::new_readonly(transfer.authority.key(), true),
+            AccountMeta::new_readonly(transfer.new_owner.key(), false),
+            AccountMeta::new_readonly(transfer.system_program.key(), false),
+            AccountMeta::new_readonly(MPL_CORE_ID, false), // No log wrapper
+        ],
+        // TransferV1 without a compression proof
+        data: vec![TRANSFER_V1_DISCRIMINATOR, 0],
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            transfer.asset,
+            collection,
+            transfer.payer,
+            transfer.authority,
+            transfer.new_owner,
+            transfer.system_program,
+            transfer.core_program,
+        ],
+        signer_seeds.unwrap_or_default(),
+    )?;
+    Ok(())
+}
\ No newline at end of file
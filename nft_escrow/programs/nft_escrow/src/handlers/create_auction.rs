@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::MetadataAccount,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    listing_collection, require_listing_collection, require_no_transfer_fee, require_strict_nft,
+    transfer_tokens,
+};
+use crate::{
+    error::ErrorCode,
+    state::{Auction, MakerProfile, MarketplaceConfig},
+};
+
+#[derive(Accounts)]
+pub struct CreateAuction<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user auctioning the NFT (pays rent, signs transaction)
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The NFT mint being auctioned
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Metadata of the NFT (proves collection membership; only when the marketplace
+    /// restricts listings to a collection)
+    #[account(
+        seeds = [
+            b"metadata",
+            anchor_spl::metadata::ID.as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = anchor_spl::metadata::ID,
+        bump,
+    )]
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// The token mint bids are paid in
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Seller's NFT token account (must have exactly 1 NFT)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Marketplace settings (storefront collection restriction)
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Seller's profile (allocates the next auction ID)
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + MakerProfile::INIT_SPACE,
+        seeds = [b"maker_profile", seller.key().as_ref()],
+        bump
+    )]
+    pub maker_profile: Account<'info, MakerProfile>,
+
+    /// The auction account (PDA) storing auction details
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Auction::INIT_SPACE,
+        seeds = [
+            b"auction",
+            seller.key().as_ref(),
+            maker_profile.offer_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Vault to hold the NFT (owned by auction PDA)
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault to hold the highest bid (owned by auction PDA)
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Create an English auction by escrowing an NFT
+pub fn create_auction(
+    ctx: Context<CreateAuction>,
+    reserve_price: u64,
+    min_bid_increment: u64,
+    end_time: i64,
+    extension_window: i64,
+) -> Result<()> {
+    // Validate auction parameters (bounded so bids cannot stay locked indefinitely)
+    let duration = end_time
+        .checked_sub(Clock::get()?.unix_timestamp)
+        .ok_or(ErrorCode::InvalidAuction)?;
+    require!(
+        duration > 0
+            && duration <= Auction::MAX_DURATION
+            && (0..=Auction::MAX_EXTENSION_WINDOW).contains(&extension_window)
+            && min_bid_increment > 0,
+        ErrorCode::InvalidAuction
+    );
+
+    // Validate the NFT is a real single-edition mint and bids arrive in full
+    require_strict_nft(&ctx.accounts.nft_mint)?;
+    require_no_transfer_fee(&ctx.accounts.payment_mint)?;
+
+    // Validate the NFT is a verified member of the marketplace's collection
+    let collection = listing_collection(None, &ctx.accounts.config)?;
+    require_listing_collection(ctx.accounts.nft_metadata.as_deref(), collection.as_ref())?;
+
+    // Validate seller has exactly 1 NFT (standard NFT has amount = 1)
+    require!(
+        ctx.accounts.seller_nft_account.amount == 1,
+        ErrorCode::FailedNftTransfer
+    );
+
+    // Transfer NFT from seller to vault (escrow it)
+    transfer_tokens(
+        &ctx.accounts.seller_nft_account,
+        &ctx.accounts.nft_vault,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        None, // Seller is regular signer, not PDA
+    )
+    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+    // Allocate the next auction ID from the seller's profile
+    let maker_profile = &mut ctx.accounts.maker_profile;
+    let auction_id = maker_profile.offer_count;
+    maker_profile.maker = ctx.accounts.seller.key();
+    maker_profile.offer_count = auction_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_profile.bump = ctx.bumps.maker_profile;
+
+    // Save auction details to auction account
+    ctx.accounts.auction.set_inner(Auction {
+        auction_id,
+        seller: ctx.accounts.seller.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        payment_mint: ctx.accounts.payment_mint.key(),
+        reserve_price,
+        min_bid_increment,
+        end_time,
+        extension_window,
+        highest_bid: 0,
+        highest_bidder: None,
+        bump: ctx.bumps.auction,
+    });
+
+    msg!(
+        "Auction created: ID={}, Reserve={}tokens, Ends={}",
+        auction_id,
+        reserve_price,
+        end_time
+    );
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, program::NftEscrow, state::MarketplaceConfig};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The program's upgrade authority (becomes the config admin)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The marketplace config account (singleton PDA)
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + MarketplaceConfig::INIT_SPACE,
+        seeds = [b"marketplace_config"],
+        bump
+    )]
+    pub config: Account<'info, MarketplaceConfig>,
+
+    /// This program (used to locate its program data account)
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()),
+    )]
+    pub program: Program<'info, NftEscrow>,
+
+    /// Program data account (proves the admin is the upgrade authority)
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Create the marketplace config with its fee and treasury
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    fee_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    // Validate fee does not exceed the 10% cap (MAX_FEE_BPS)
+    require!(
+        fee_bps <= MarketplaceConfig::MAX_FEE_BPS,
+        ErrorCode::InvalidFeeBps
+    );
+
+    // Save config details to config account
+    ctx.accounts.config.set_inner(MarketplaceConfig {
+        admin: ctx.accounts.admin.key(),
+        fee_bps,
+        treasury,
+        collection: None,
+        bump: ctx.bumps.config,
+    });
+
+    msg!("Marketplace config initialized: Fee={}bps, Treasury={}", fee_bps, treasury);
+    Ok(())
+}
\ No newline at end of file
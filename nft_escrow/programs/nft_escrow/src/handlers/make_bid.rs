@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{require_no_transfer_fee, transfer_tokens};
+use crate::{
+    error::ErrorCode,
+    state::{CollectionBid, MakerProfile},
+};
+
+#[derive(Accounts)]
+pub struct MakeBid<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user placing the bid (pays rent, escrows payment)
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The verified collection the buyer wants an NFT from
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    /// The token mint the buyer pays with
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Buyer's payment token account (source of escrowed payment)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Buyer's profile (allocates the next bid ID)
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + MakerProfile::INIT_SPACE,
+        seeds = [b"maker_profile", buyer.key().as_ref()],
+        bump
+    )]
+    pub maker_profile: Account<'info, MakerProfile>,
+
+    /// The bid account (PDA) storing bid details
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + CollectionBid::INIT_SPACE,
+        seeds = [
+            b"collection_bid",
+            buyer.key().as_ref(),
+            maker_profile.offer_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub bid: Account<'info, CollectionBid>,
+
+    /// Vault to hold the escrowed payment (owned by bid PDA)
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bid,
+        associated_token::token_program = token_program,
+    )]
+    pub bid_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Place a collection-wide bid by escrowing payment tokens
+pub fn make_bid(ctx: Context<MakeBid>, token_amount: u64) -> Result<()> {
+    // Validate token amount is greater than zero
+    require!(token_amount > 0, ErrorCode::InvalidTokenAmount);
+
+    // Validate the vault will receive the full bid (fills pay out token_amount)
+    require_no_transfer_fee(&ctx.accounts.payment_mint)?;
+
+    // Transfer payment from buyer to vault (escrow it)
+    transfer_tokens(
+        &ctx.accounts.buyer_payment_account,
+        &ctx.accounts.bid_vault,
+        token_amount,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.token_program,
+        None, // Buyer is regular signer, not PDA
+    )
+    .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+
+    // Allocate the next bid ID from the buyer's profile
+    let maker_profile = &mut ctx.accounts.maker_profile;
+    let bid_id = maker_profile.offer_count;
+    maker_profile.maker = ctx.accounts.buyer.key();
+    maker_profile.offer_count = bid_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_profile.bump = ctx.bumps.maker_profile;
+
+    // Save bid details to bid account
+    ctx.accounts.bid.set_inner(CollectionBid {
+        bid_id,
+        buyer: ctx.accounts.buyer.key(),
+        collection_mint: ctx.accounts.collection_mint.key(),
+        payment_mint: ctx.accounts.payment_mint.key(),
+        token_amount,
+        bump: ctx.bumps.bid,
+    });
+
+    msg!(
+        "Collection bid created: ID={}, Collection={}, Payment={}tokens",
+        bid_id,
+        ctx.accounts.collection_mint.key(),
+        token_amount
+    );
+    Ok(())
+}
\ No newline at end of file
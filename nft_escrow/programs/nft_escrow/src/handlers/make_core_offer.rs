@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{Mint, TokenInterface},
+};
+
+use super::core_asset::{
+    read_core_asset, require_core_collection, transfer_core_asset, CoreTransfer, MPL_CORE_ID,
+};
+use crate::{
+    error::ErrorCode,
+    state::{CoreOffer, MakerProfile, MarketplaceConfig, PaymentMode},
+};
+
+#[derive(Accounts)]
+pub struct MakeCoreOffer<'info> {
+    /// Metaplex Core program (owns the asset)
+    /// CHECK: Address checked against the Core program ID
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user creating the offer (pays rent, signs transaction)
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The MPL Core asset being offered (must be owned by maker)
+    /// CHECK: Owner and layout validated in the handler
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// Core collection of the asset (omit if the asset is not in a collection)
+    /// CHECK: Must match the asset's collection; validated in the handler
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    /// The token mint that maker wants in payment (omit to be paid in native SOL)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Marketplace settings (storefront collection restriction)
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Maker's profile (allocates the next offer ID)
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + MakerProfile::INIT_SPACE,
+        seeds = [b"maker_profile", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_profile: Account<'info, MakerProfile>,
+
+    /// The offer account (PDA) storing offer details and holding the asset
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + CoreOffer::INIT_SPACE,
+        seeds = [
+            b"core_offer",
+            maker.key().as_ref(),
+            maker_profile.offer_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub offer: Account<'info, CoreOffer>,
+}
+
+/// Create an MPL Core offer by transferring the asset to the offer PDA in
+/// exchange for tokens
+pub fn make_core_offer(
+    ctx: Context<MakeCoreOffer>,
+    token_amount: u64,
+    expires_at: i64,
+    allowed_taker: Option<Pubkey>,
+) -> Result<()> {
+    // Validate token amount is greater than zero
+    require!(token_amount > 0, ErrorCode::InvalidTokenAmount);
+
+    // Validate offer expires in the future
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        ErrorCode::InvalidExpiry
+    );
+
+    // Validate maker owns the asset and passed its collection
+    let asset = read_core_asset(&ctx.accounts.asset)?;
+    require_keys_eq!(
+        asset.owner,
+        ctx.accounts.maker.key(),
+        ErrorCode::FailedNftTransfer
+    );
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+    require_core_collection(collection.as_ref(), asset.collection)?;
+
+    // Validate the asset belongs to the marketplace's collection (if restricted)
+    if let Some(marketplace_collection) = ctx.accounts.config.collection {
+        require!(
+            asset.collection == Some(marketplace_collection),
+            ErrorCode::CollectionMismatch
+        );
+    }
+
+    // Step 1: Transfer the asset from maker to the offer PDA (escrow it)
+    transfer_core_asset(
+        CoreTransfer {
+            asset: ctx.accounts.asset.to_account_info(),
+            collection,
+            payer: ctx.accounts.maker.to_account_info(),
+            authority: ctx.accounts.maker.to_account_info(),
+            new_owner: ctx.accounts.offer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            core_program: ctx.accounts.mpl_core_program.to_account_info(),
+        },
+        None, // Maker is regular signer, not PDA
+    )
+    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+    // Allocate the next offer ID from the maker's profile
+    let maker_profile = &mut ctx.accounts.maker_profile;
+    let offer_id = maker_profile.offer_count;
+    maker_profile.maker = ctx.accounts.maker.key();
+    maker_profile.offer_count = offer_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_profile.bump = ctx.bumps.maker_profile;
+
+    // No payment mint means the taker pays in native SOL
+    let (payment_mint, payment_mode) = match &ctx.accounts.payment_mint {
+        Some(payment_mint) => (payment_mint.key(), PaymentMode::Token),
+        None => (native_mint::ID, PaymentMode::NativeSol),
+    };
+
+    // Save offer details to offer account
+    ctx.accounts.offer.set_inner(CoreOffer {
+        offer_id,
+        maker: ctx.accounts.maker.key(),
+        asset: ctx.accounts.asset.key(),
+        collection: asset.collection,
+        payment_mint,
+        payment_mode,
+        token_amount,
+        expires_at,
+        allowed_taker,
+        bump: ctx.bumps.offer,
+    });
+
+    msg!(
+        "Core offer created: ID={}, Payment={}tokens",
+        offer_id,
+        token_amount
+    );
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use super::make_offer::{create_offer, MakeOffer};
+use crate::{error::ErrorCode, state::CustodyMode};
+
+/// Create a non-custodial listing: the NFT stays in the maker's wallet and the
+/// offer PDA is approved as its delegate (pass no nft_vault)
+/// A `collection` restricts the listing to verified members of that collection.
+pub fn make_delegate_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
+    token_amount: u64,
+    expires_at: i64,
+    allowed_taker: Option<Pubkey>,
+    collection: Option<Pubkey>,
+) -> Result<()> {
+    // Validate token amount is greater than zero
+    require!(token_amount > 0, ErrorCode::InvalidTokenAmount);
+
+    create_offer(
+        ctx,
+        token_amount,
+        None,
+        CustodyMode::Delegate,
+        expires_at,
+        allowed_taker,
+        collection,
+    )
+}
\ No newline at end of file
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use super::make_offer::{create_offer, MakeOffer};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, DutchAuction},
+};
+
+/// Create a Dutch auction listing whose price falls from start to end price
+/// A `collection` restricts the listing to verified members of that collection.
+pub fn make_dutch_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
+    auction: DutchAuction,
+    expires_at: i64,
+    allowed_taker: Option<Pubkey>,
+    collection: Option<Pubkey>,
+) -> Result<()> {
+    // Validate price falls to a non-zero end price over a positive duration
+    require!(
+        auction.end_price > 0
+            && auction.start_price >= auction.end_price
+            && auction.duration > 0,
+        ErrorCode::InvalidDutchAuction
+    );
+
+    create_offer(
+        ctx,
+        auction.start_price,
+        Some(auction),
+        CustodyMode::Vault,
+        expires_at,
+        allowed_taker,
+        collection,
+    )
+}
\ No newline at end of file
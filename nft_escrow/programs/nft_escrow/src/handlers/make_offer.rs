@@ -1,20 +1,31 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
+    metadata::MetadataAccount,
+    token::spl_token::native_mint,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use super::shared::transfer_tokens;
-use crate::{error::ErrorCode, state::NftOffer};
+use super::pnft::*;
+use super::shared::{
+    approve_nft_delegate, listing_collection, require_listing_collection, require_strict_nft,
+    BundleTransfer,
+};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, DutchAuction, MakerProfile, MarketplaceConfig, NftOffer, PaymentMode},
+};
 
 #[derive(Accounts)]
-#[instruction(offer_id: u64)]
 pub struct MakeOffer<'info> {
     /// Program for managing associated token accounts
     pub associated_token_program: Program<'info, AssociatedToken>,
 
-    /// Token program (SPL Token or Token-2022)
-    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
 
     /// System program for creating accounts
     pub system_program: Program<'info, System>,
@@ -25,83 +36,237 @@ pub struct MakeOffer<'info> {
 
     /// The NFT mint being offered
     #[account(
-        mint::token_program = token_program,
+        mint::token_program = nft_token_program,
     )]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    /// The token mint that maker wants in payment
+    /// Metadata of the NFT (proves collection membership; only for collection-restricted
+    /// listings)
     #[account(
-        mint::token_program = token_program,
+        seeds = [
+            b"metadata",
+            anchor_spl::metadata::ID.as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = anchor_spl::metadata::ID,
+        bump,
     )]
-    pub payment_mint: InterfaceAccount<'info, Mint>,
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// The token mint that maker wants in payment (omit to be paid in native SOL)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
 
     /// Maker's NFT token account (must have exactly 1 NFT)
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = maker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = nft_token_program,
     )]
     pub maker_nft_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// Marketplace settings (storefront collection restriction)
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Maker's profile (allocates the next offer ID)
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + MakerProfile::INIT_SPACE,
+        seeds = [b"maker_profile", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_profile: Account<'info, MakerProfile>,
+
     /// The offer account (PDA) storing offer details
     #[account(
         init,
         payer = maker,
         space = 8 + NftOffer::INIT_SPACE,
-        seeds = [b"nft_offer", offer_id.to_le_bytes().as_ref()],
+        seeds = [
+            b"nft_offer",
+            maker.key().as_ref(),
+            maker_profile.offer_count.to_le_bytes().as_ref(),
+        ],
         bump
     )]
     pub offer: Account<'info, NftOffer>,
 
-    /// Vault to hold the NFT (owned by offer PDA)
+    /// Vault to hold the NFT (owned by offer PDA; omit for delegate listings)
     #[account(
         init,
         payer = maker,
         associated_token::mint = nft_mint,
         associated_token::authority = offer,
-        associated_token::token_program = token_program,
+        associated_token::token_program = nft_token_program,
     )]
-    pub nft_vault: InterfaceAccount<'info, TokenAccount>,
+    pub nft_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token Metadata accounts of a programmable NFT (omit for regular NFTs)
+    pub pnft: PnftAccounts<'info>,
 }
 
 /// Create an NFT offer by escrowing an NFT in exchange for tokens
-pub fn make_offer(
-    ctx: Context<MakeOffer>,
-    offer_id: u64,
+/// A `collection` restricts the listing to verified members of that collection.
+/// Remaining accounts (bundle listings only): nft_mint, maker_nft_account and
+/// nft_vault (ATA of the offer) of each extra NFT, all on the NFT token program,
+/// plus its metadata when the offer or the marketplace names a collection
+pub fn make_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
     token_amount: u64,
+    expires_at: i64,
+    allowed_taker: Option<Pubkey>,
+    collection: Option<Pubkey>,
 ) -> Result<()> {
     // Validate token amount is greater than zero
     require!(token_amount > 0, ErrorCode::InvalidTokenAmount);
 
+    create_offer(
+        ctx,
+        token_amount,
+        None,
+        CustodyMode::Vault,
+        expires_at,
+        allowed_taker,
+        collection,
+    )
+}
+
+/// Escrow the maker's NFT (and any bundle NFTs), or approve the offer as its
+/// delegate, and save the offer
+/// Shared by fixed-price, Dutch auction and delegate listings
+pub(crate) fn create_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
+    token_amount: u64,
+    dutch_auction: Option<DutchAuction>,
+    custody: CustodyMode,
+    expires_at: i64,
+    allowed_taker: Option<Pubkey>,
+    collection: Option<Pubkey>,
+) -> Result<()> {
+    // Validate offer expires in the future
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        ErrorCode::InvalidExpiry
+    );
+
     // Validate maker has exactly 1 NFT (standard NFT has amount = 1)
     require!(
         ctx.accounts.maker_nft_account.amount == 1,
         ErrorCode::FailedNftTransfer
     );
 
-    // Transfer NFT from maker to vault (escrow it)
-    transfer_tokens(
-        &ctx.accounts.maker_nft_account,
-        &ctx.accounts.nft_vault,
-        1, // NFTs have amount = 1
-        &ctx.accounts.nft_mint,
-        &ctx.accounts.maker.to_account_info(),
-        &ctx.accounts.token_program,
-        None, // Maker is regular signer, not PDA
-    )
-    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+    // Validate the mint is a real NFT, not a fungible token held as one unit
+    require_strict_nft(&ctx.accounts.nft_mint)?;
+
+    // Validate the NFT is a verified member of the offer's and the marketplace's
+    // collection
+    let collection = listing_collection(collection, &ctx.accounts.config)?;
+    require_listing_collection(ctx.accounts.nft_metadata.as_deref(), collection.as_ref())?;
+
+    let bundle_mints = match (custody, &ctx.accounts.nft_vault) {
+        (CustodyMode::Vault, Some(nft_vault)) => {
+            // Transfer NFT from maker to vault (escrow it)
+            transfer_nft(
+                NftTransfer {
+                    from: &ctx.accounts.maker_nft_account,
+                    from_owner: ctx.accounts.maker.to_account_info(),
+                    to: nft_vault,
+                    to_owner: ctx.accounts.offer.to_account_info(),
+                    mint: &ctx.accounts.nft_mint,
+                    payer: ctx.accounts.maker.to_account_info(),
+                    token_program: &ctx.accounts.nft_token_program,
+                    associated_token_program: &ctx.accounts.associated_token_program,
+                    system_program: &ctx.accounts.system_program,
+                    pnft: &ctx.accounts.pnft,
+                },
+                None, // Maker is regular signer, not PDA,
+            )
+            .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+            // Escrow the extra NFTs of a bundle listing
+            let bundle = BundleTransfer {
+                offer: ctx.accounts.offer.to_account_info(),
+                payer: ctx.accounts.maker.to_account_info(),
+                token_program: &ctx.accounts.nft_token_program,
+                associated_token_program: &ctx.accounts.associated_token_program,
+                system_program: &ctx.accounts.system_program,
+            };
+            bundle.escrow(
+                ctx.remaining_accounts,
+                &ctx.accounts.maker.to_account_info(),
+                collection.as_ref(),
+            )?
+        }
+        (CustodyMode::Delegate, None) => {
+            // Bundles are always escrowed
+            require!(
+                ctx.remaining_accounts.is_empty(),
+                ErrorCode::InvalidBundleAccounts
+            );
+
+            // pNFTs stay frozen in the wallet and cannot be delegated this way
+            require!(
+                ctx.accounts.pnft.edition.is_none(),
+                ErrorCode::InvalidCustodyAccounts
+            );
+
+            // Approve the offer PDA to move the NFT out of the maker's wallet
+            approve_nft_delegate(
+                &ctx.accounts.maker_nft_account,
+                &ctx.accounts.nft_mint,
+                &ctx.accounts.offer.to_account_info(),
+                &ctx.accounts.maker.to_account_info(),
+                &ctx.accounts.nft_token_program,
+            )
+            .map_err(|_| ErrorCode::FailedNftTransfer)?;
+            Vec::new()
+        }
+        _ => return err!(ErrorCode::InvalidCustodyAccounts),
+    };
+
+    // Allocate the next offer ID from the maker's profile
+    let maker_profile = &mut ctx.accounts.maker_profile;
+    let offer_id = maker_profile.offer_count;
+    maker_profile.maker = ctx.accounts.maker.key();
+    maker_profile.offer_count = offer_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_profile.bump = ctx.bumps.maker_profile;
+
+    // No payment mint means the taker pays in native SOL
+    let (payment_mint, payment_mode) = match &ctx.accounts.payment_mint {
+        Some(payment_mint) => (payment_mint.key(), PaymentMode::Token),
+        None => (native_mint::ID, PaymentMode::NativeSol),
+    };
 
     // Save offer details to offer account
     ctx.accounts.offer.set_inner(NftOffer {
         offer_id,
         maker: ctx.accounts.maker.key(),
         nft_mint: ctx.accounts.nft_mint.key(),
-        payment_mint: ctx.accounts.payment_mint.key(),
+        bundle_mints,
+        custody,
+        payment_mint,
+        payment_mode,
         token_amount,
+        dutch_auction,
+        expires_at,
+        allowed_taker,
+        collection,
         bump: ctx.bumps.offer,
     });
 
-    msg!("NFT offer created: ID={}, Payment={}tokens", offer_id, token_amount);
+    msg!(
+        "NFT offer created: ID={}, NFTs={}, Payment={}tokens",
+        offer_id,
+        ctx.accounts.offer.bundle_mints.len() + 1,
+        token_amount
+    );
     Ok(())
 }
\ No newline at end of file
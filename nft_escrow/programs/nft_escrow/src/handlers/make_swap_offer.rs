@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::MetadataAccount,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    listing_collection, require_listing_collection, require_no_transfer_fee, require_strict_nft,
+    transfer_tokens,
+};
+use crate::{
+    error::ErrorCode,
+    state::{MakerProfile, MarketplaceConfig, SwapOffer, SwapRequest, SwapTopUp, TopUpDirection},
+};
+
+#[derive(Accounts)]
+pub struct MakeSwapOffer<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user creating the swap offer (pays rent, signs transaction)
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The NFT mint being offered
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Metadata of the NFT (proves collection membership; only when the marketplace
+    /// restricts listings to a collection)
+    #[account(
+        seeds = [
+            b"metadata",
+            anchor_spl::metadata::ID.as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = anchor_spl::metadata::ID,
+        bump,
+    )]
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// The token mint of the top-up (omit for swaps without a top-up)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Maker's NFT token account (must have exactly 1 NFT)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub maker_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Maker's payment token account (source of a maker-paid top-up)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (storefront collection restriction)
+    #[account(
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Maker's profile (allocates the next offer ID)
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + MakerProfile::INIT_SPACE,
+        seeds = [b"maker_profile", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_profile: Account<'info, MakerProfile>,
+
+    /// The swap offer account (PDA) storing offer details
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + SwapOffer::INIT_SPACE,
+        seeds = [
+            b"swap_offer",
+            maker.key().as_ref(),
+            maker_profile.offer_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub swap_offer: Account<'info, SwapOffer>,
+
+    /// Vault to hold the NFT (owned by swap offer PDA)
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = nft_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault to hold a maker-paid top-up (owned by swap offer PDA)
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub top_up_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+/// Create an NFT-for-NFT swap offer by escrowing an NFT
+/// A maker-paid top-up is escrowed alongside it.
+pub fn make_swap_offer(
+    ctx: Context<MakeSwapOffer>,
+    requested: SwapRequest,
+    top_up: Option<SwapTopUp>,
+    expires_at: i64,
+) -> Result<()> {
+    // Validate offer expires in the future
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        ErrorCode::InvalidExpiry
+    );
+
+    // Validate top-up is non-zero and names a payment mint that delivers it in full
+    if let Some(top_up) = &top_up {
+        let Some(payment_mint) = ctx.accounts.payment_mint.as_ref() else {
+            return err!(ErrorCode::InvalidSwapTopUp);
+        };
+        require!(top_up.amount > 0, ErrorCode::InvalidSwapTopUp);
+        require_no_transfer_fee(payment_mint)?;
+    }
+
+    // Validate the NFT is a real single-edition mint
+    require_strict_nft(&ctx.accounts.nft_mint)?;
+
+    // Validate the NFT is a verified member of the marketplace's collection
+    let collection = listing_collection(None, &ctx.accounts.config)?;
+    require_listing_collection(ctx.accounts.nft_metadata.as_deref(), collection.as_ref())?;
+
+    // Validate maker has exactly 1 NFT (standard NFT has amount = 1)
+    require!(
+        ctx.accounts.maker_nft_account.amount == 1,
+        ErrorCode::FailedNftTransfer
+    );
+
+    // Step 1: Transfer NFT from maker to vault (escrow it)
+    transfer_tokens(
+        &ctx.accounts.maker_nft_account,
+        &ctx.accounts.nft_vault,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        None, // Maker is regular signer, not PDA
+    )
+    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+    // Step 2: Escrow a maker-paid top-up
+    if let Some(SwapTopUp {
+        amount,
+        direction: TopUpDirection::MakerPays,
+    }) = top_up
+    {
+        let (Some(mint), Some(maker_payment_account), Some(top_up_vault)) = (
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.maker_payment_account.as_deref(),
+            ctx.accounts.top_up_vault.as_deref(),
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccount);
+        };
+        transfer_tokens(
+            maker_payment_account,
+            top_up_vault,
+            amount,
+            mint,
+            &ctx.accounts.maker.to_account_info(),
+            &ctx.accounts.payment_token_program,
+            None, // Maker is regular signer, not PDA
+        )
+        .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+    }
+
+    // Allocate the next offer ID from the maker's profile
+    let maker_profile = &mut ctx.accounts.maker_profile;
+    let offer_id = maker_profile.offer_count;
+    maker_profile.maker = ctx.accounts.maker.key();
+    maker_profile.offer_count = offer_id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_profile.bump = ctx.bumps.maker_profile;
+
+    // Save offer details to swap offer account
+    ctx.accounts.swap_offer.set_inner(SwapOffer {
+        offer_id,
+        maker: ctx.accounts.maker.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        requested,
+        payment_mint: top_up
+            .and(ctx.accounts.payment_mint.as_ref())
+            .map(|mint| mint.key()),
+        top_up,
+        expires_at,
+        bump: ctx.bumps.swap_offer,
+    });
+
+    msg!("Swap offer created: ID={}", offer_id);
+    Ok(())
+}
\ No newline at end of file
@@ -1,11 +1,80 @@
 pub mod make_offer;
 pub use make_offer::*;
 
+pub mod make_dutch_offer;
+pub use make_dutch_offer::*;
+
+pub mod make_delegate_offer;
+pub use make_delegate_offer::*;
+
 pub mod take_offer;
 pub use take_offer::*;
 
+pub mod take_offers_batch;
+pub use take_offers_batch::*;
+
 pub mod cancel_offer;
 pub use cancel_offer::*;
 
+pub mod update_offer;
+pub use update_offer::*;
+
+pub mod close_expired_offer;
+pub use close_expired_offer::*;
+
+pub mod make_bid;
+pub use make_bid::*;
+
+pub mod accept_bid;
+pub use accept_bid::*;
+
+pub mod cancel_bid;
+pub use cancel_bid::*;
+
+pub mod create_auction;
+pub use create_auction::*;
+
+pub mod place_bid;
+pub use place_bid::*;
+
+pub mod settle_auction;
+pub use settle_auction::*;
+
+pub mod cancel_auction;
+pub use cancel_auction::*;
+
+pub mod make_swap_offer;
+pub use make_swap_offer::*;
+
+pub mod take_swap_offer;
+pub use take_swap_offer::*;
+
+pub mod cancel_swap_offer;
+pub use cancel_swap_offer::*;
+
+pub mod make_core_offer;
+pub use make_core_offer::*;
+
+pub mod take_core_offer;
+pub use take_core_offer::*;
+
+pub mod cancel_core_offer;
+pub use cancel_core_offer::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod set_config_collection;
+pub use set_config_collection::*;
+
+pub mod pnft;
+pub use pnft::*;
+
+pub mod core_asset;
+pub use core_asset::*;
+
 pub mod shared;
 pub use shared::*;
\ No newline at end of file
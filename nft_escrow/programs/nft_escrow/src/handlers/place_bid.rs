@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::transfer_tokens;
+use crate::{error::ErrorCode, state::Auction};
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user placing the bid (escrows payment)
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// The token mint bids are paid in
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// Bidder's payment token account (source of the bid)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = bidder,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The current highest bidder (omit for the first bid)
+    /// CHECK: Validated against auction.highest_bidder in the handler
+    pub previous_bidder: Option<UncheckedAccount<'info>>,
+
+    /// Previous highest bidder's payment token account (receives their refund)
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = payment_mint,
+        associated_token::authority = previous_bidder,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub previous_bidder_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The auction account
+    #[account(
+        mut,
+        has_one = payment_mint,
+        seeds = [
+            b"auction",
+            auction.seller.as_ref(),
+            auction.auction_id.to_le_bytes().as_ref(),
+        ],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// Vault holding the highest bid
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Place a bid by escrowing tokens; the previous highest bid is refunded
+pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    // Validate auction is still open
+    require!(
+        !ctx.accounts.auction.has_ended(now),
+        ErrorCode::AuctionEnded
+    );
+
+    // Validate bidder is not the seller
+    require_keys_neq!(
+        ctx.accounts.bidder.key(),
+        ctx.accounts.auction.seller,
+        ErrorCode::SellerCannotBid
+    );
+
+    // Validate bid beats the highest bid by the minimum increment
+    require!(
+        amount >= ctx.accounts.auction.min_next_bid()?,
+        ErrorCode::BidTooLow
+    );
+
+    // Step 1: Escrow the new bid in the payment vault
+    transfer_tokens(
+        &ctx.accounts.bidder_payment_account,
+        &ctx.accounts.payment_vault,
+        amount,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.bidder.to_account_info(),
+        &ctx.accounts.payment_token_program,
+        None, // Bidder is regular signer
+    )
+    .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+
+    // Step 2: Refund the previous highest bidder
+    if let Some(previous_bidder) = ctx.accounts.auction.highest_bidder {
+        let (Some(previous_bidder_account), Some(refund_account)) = (
+            ctx.accounts.previous_bidder.as_ref(),
+            ctx.accounts.previous_bidder_payment_account.as_deref(),
+        ) else {
+            return err!(ErrorCode::BidderMismatch);
+        };
+        require_keys_eq!(
+            previous_bidder_account.key(),
+            previous_bidder,
+            ErrorCode::BidderMismatch
+        );
+
+        let auction_seeds = &[
+            b"auction",
+            ctx.accounts.auction.seller.as_ref(),
+            &ctx.accounts.auction.auction_id.to_le_bytes()[..],
+            &[ctx.accounts.auction.bump],
+        ];
+        let signer_seeds = &[&auction_seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.payment_vault,
+            refund_account,
+            ctx.accounts.auction.highest_bid,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.auction.to_account_info(),
+            &ctx.accounts.payment_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedBidRefund)?;
+    }
+
+    // Step 3: Record the new highest bid and extend a closing auction
+    let auction = &mut ctx.accounts.auction;
+    auction.highest_bid = amount;
+    auction.highest_bidder = Some(ctx.accounts.bidder.key());
+    auction.extend_for_bid(now)?;
+
+    msg!(
+        "Bid placed: Auction={}, Bid={}tokens, Ends={}",
+        auction.auction_id,
+        amount,
+        auction.end_time
+    );
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{mpl_token_metadata::instructions::TransferV1CpiBuilder, Metadata},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::transfer_tokens;
+use crate::error::ErrorCode;
+
+/// Token Metadata accounts needed to move a programmable NFT (pNFT)
+/// Omit them all for regular NFTs; Token Metadata validates each one.
+#[derive(Accounts)]
+pub struct PnftAccounts<'info> {
+    /// Metaplex Token Metadata program
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// Metadata of the pNFT
+    /// CHECK: Validated by Token Metadata during the transfer
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// Master edition of the pNFT
+    /// CHECK: Validated by Token Metadata during the transfer
+    pub edition: Option<UncheckedAccount<'info>>,
+
+    /// Token record of the account the pNFT leaves
+    /// CHECK: Validated by Token Metadata during the transfer
+    #[account(mut)]
+    pub owner_token_record: Option<UncheckedAccount<'info>>,
+
+    /// Token record of the account the pNFT enters (created if missing)
+    /// CHECK: Validated by Token Metadata during the transfer
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+
+    /// Instructions sysvar (read by Token Metadata)
+    /// CHECK: Address checked against the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+
+    /// Token Authorization Rules program (only if the pNFT has a rule set)
+    /// CHECK: Validated by Token Metadata during the transfer
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// Rule set of the pNFT (only if it has one)
+    /// CHECK: Validated by Token Metadata during the transfer
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts to move a single NFT between token accounts
+pub struct NftTransfer<'a, 'info> {
+    pub from: &'a InterfaceAccount<'info, TokenAccount>,
+    /// Owner of `from` (or its delegate), signs the transfer
+    pub from_owner: AccountInfo<'info>,
+    pub to: &'a InterfaceAccount<'info, TokenAccount>,
+    pub to_owner: AccountInfo<'info>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    /// Pays rent for the destination token record of a pNFT
+    pub payer: AccountInfo<'info>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub associated_token_program: &'a Program<'info, AssociatedToken>,
+    pub system_program: &'a Program<'info, System>,
+    pub pnft: &'a PnftAccounts<'info>,
+}
+
+/// Move an NFT from `from` to `to`, signed by `from_owner`
+/// pNFTs (edition account passed) go through a Token Metadata TransferV1 CPI
+/// that enforces their rule set; regular NFTs use `transfer_checked`.
+pub fn transfer_nft(transfer: NftTransfer, signer_seeds: Option<&[&[&[u8]]]>) -> Result<()> {
+    let pnft = transfer.pnft;
+    let Some(edition) = &pnft.edition else {
+        return transfer_tokens(
+            transfer.from,
+            transfer.to,
+            1, // NFTs have amount = 1
+            transfer.mint,
+            &transfer.from_owner,
+            transfer.token_program,
+            signer_seeds,
+        );
+    };
+
+    let (
+        Some(token_metadata_program),
+        Some(metadata),
+        Some(owner_token_record),
+        Some(destination_token_record),
+        Some(sysvar_instructions),
+    ) = (
+        &pnft.token_metadata_program,
+        &pnft.metadata,
+        &pnft.owner_token_record,
+        &pnft.destination_token_record,
+        &pnft.sysvar_instructions,
+    )
+    else {
+        return err!(ErrorCode::MissingPnftAccount);
+    };
+
+    let program = token_metadata_program.to_account_info();
+    let from = transfer.from.to_account_info();
+    let to = transfer.to.to_account_info();
+    let mint = transfer.mint.to_account_info();
+    let token_program = transfer.token_program.to_account_info();
+    let associated_token_program = transfer.associated_token_program.to_account_info();
+    let system_program = transfer.system_program.to_account_info();
+
+    let mut cpi = TransferV1CpiBuilder::new(&program);
+    cpi.token(&from)
+        .token_owner(&transfer.from_owner)
+        .destination_token(&to)
+        .destination_owner(&transfer.to_owner)
+        .mint(&mint)
+        .metadata(metadata)
+        .edition(Some(edition))
+        .token_record(Some(owner_token_record))
+        .destination_token_record(Some(destination_token_record))
+        .authority(&transfer.from_owner)
+        .payer(&transfer.payer)
+        .system_program(&system_program)
+        .sysvar_instructions(sysvar_instructions)
+        .spl_token_program(&token_program)
+        .spl_ata_program(&associated_token_program)
+        .authorization_rules_program(pnft.authorization_rules_program.as_deref())
+        .authorization_rules(pnft.authorization_rules.as_deref())
+        .amount(1); // NFTs have amount = 1
+
+    match signer_seeds {
+        Some(seeds) => cpi.invoke_signed(seeds)?,
+        None => cpi.invoke()?,
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+use super::update_config::UpdateConfig;
+
+/// Restrict listings to verified members of a collection (`None` lifts the restriction)
+pub fn set_config_collection(ctx: Context<UpdateConfig>, collection: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.config.collection = collection;
+
+    msg!("Marketplace collection restriction set: {:?}", collection);
+    Ok(())
+}
\ No newline at end of file
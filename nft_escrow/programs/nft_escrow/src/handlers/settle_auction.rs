@@ -0,0 +1,327 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    close_payment_vault, close_token_account, creator_royalties, settle_sale, transfer_tokens,
+    SalePayment, TokenPayment,
+};
+use crate::{
+    error::ErrorCode,
+    state::{Auction, MarketplaceConfig},
+};
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// Metaplex Token Metadata program (owns the NFT metadata)
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// Anyone can settle an ended auction (pays rent for any missing token accounts)
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// The auction creator (receives payment and vault rent)
+    /// CHECK: Validated via has_one constraint on auction account
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// The highest bidder (omit when the auction has no bids)
+    /// CHECK: Validated against auction.highest_bidder in the handler
+    pub highest_bidder: Option<UncheckedAccount<'info>>,
+
+    /// The NFT mint being auctioned
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Metadata of the NFT (royalty settings; empty if the NFT has none)
+    /// CHECK: Address derived from nft_mint; only read when initialized
+    #[account(
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    /// The token mint bids are paid in
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Winner's NFT account (receives the NFT; only when the reserve is met)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = highest_bidder,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub winner_nft_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Highest bidder's payment token account (refund; reserve not met or NFT stuck in vault)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = highest_bidder,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub bidder_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Seller's NFT account (receives the NFT back; only when the reserve is not met)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub seller_nft_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Seller's payment token account (receives sale proceeds; only when the reserve is met)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub seller_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee
+    /// CHECK: Validated via has_one constraint on config account
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives marketplace fee; only when the reserve is met)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub treasury_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The auction account (will be closed)
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        has_one = nft_mint,
+        has_one = payment_mint,
+        seeds = [
+            b"auction",
+            seller.key().as_ref(),
+            auction.auction_id.to_le_bytes().as_ref(),
+        ],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Vault holding the escrowed NFT
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault holding the highest bid
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = auction,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Settle an ended auction (permissionless)
+/// If the highest bid meets the reserve the winner receives the NFT and the
+/// bid is split between fee, royalties and seller; otherwise the NFT returns
+/// to the seller and any bid is refunded. A bid is also refunded when the NFT
+/// vault has been emptied or frozen, so it can never stay locked.
+/// Remaining accounts: payment token account of each verified royalty creator,
+/// in the order they appear in the NFT metadata
+pub fn settle_auction<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+) -> Result<()> {
+    // Validate auction has ended
+    require!(
+        ctx.accounts.auction.has_ended(Clock::get()?.unix_timestamp),
+        ErrorCode::AuctionNotEnded
+    );
+
+    // Validate the highest bidder account matches the auction
+    if let Some(highest_bidder) = ctx.accounts.auction.highest_bidder {
+        require!(
+            ctx.accounts
+                .highest_bidder
+                .as_ref()
+                .is_some_and(|bidder| bidder.key() == highest_bidder),
+            ErrorCode::BidderMismatch
+        );
+    }
+
+    // Prepare PDA signer seeds for vault operations
+    let auction_seeds = &[
+        b"auction",
+        ctx.accounts.auction.seller.as_ref(),
+        &ctx.accounts.auction.auction_id.to_le_bytes()[..],
+        &[ctx.accounts.auction.bump],
+    ];
+    let signer_seeds = &[&auction_seeds[..]];
+
+    // The NFT can only be released if it is still in the vault and not frozen
+    let nft_available = ctx.accounts.nft_vault.amount == 1 && !ctx.accounts.nft_vault.is_frozen();
+
+    let highest_bid = ctx.accounts.auction.highest_bid;
+    if nft_available && ctx.accounts.auction.reserve_met() {
+        let (Some(winner_nft_account), Some(seller_account), Some(treasury_account)) = (
+            ctx.accounts.winner_nft_account.as_deref(),
+            ctx.accounts.seller_payment_account.as_deref(),
+            ctx.accounts.treasury_payment_account.as_deref(),
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccount);
+        };
+
+        // Step 1: Transfer NFT from vault to winner
+        transfer_tokens(
+            &ctx.accounts.nft_vault,
+            winner_nft_account,
+            1, // NFTs have amount = 1
+            &ctx.accounts.nft_mint,
+            &ctx.accounts.auction.to_account_info(),
+            &ctx.accounts.nft_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
+
+        // Step 2: Pay marketplace fee, creator royalties and the seller from the bid vault
+        let fee_amount = ctx.accounts.config.fee_for(highest_bid)?;
+        let royalties = creator_royalties(&ctx.accounts.nft_metadata, highest_bid)?;
+        let payment = SalePayment {
+            payer: ctx.accounts.auction.to_account_info(),
+            maker: ctx.accounts.seller.to_account_info(),
+            treasury: ctx.accounts.treasury.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token: Some(TokenPayment {
+                mint: &ctx.accounts.payment_mint,
+                payer_account: &ctx.accounts.payment_vault,
+                maker_account: seller_account,
+                treasury_account,
+                token_program: &ctx.accounts.payment_token_program,
+            }),
+            signer_seeds: Some(signer_seeds),
+        };
+        let (seller_amount, royalty_amount) = settle_sale(
+            &payment,
+            highest_bid,
+            fee_amount,
+            &royalties,
+            ctx.remaining_accounts,
+        )?;
+
+        msg!(
+            "Auction settled: NFT transferred to winner, {} paid to seller, {} fee, {} royalties",
+            seller_amount,
+            fee_amount,
+            royalty_amount
+        );
+    } else {
+        // Step 1: Return NFT from vault to seller (if it can still be moved)
+        if nft_available {
+            let Some(seller_nft_account) = ctx.accounts.seller_nft_account.as_deref() else {
+                return err!(ErrorCode::FailedNftReturn);
+            };
+            transfer_tokens(
+                &ctx.accounts.nft_vault,
+                seller_nft_account,
+                1, // NFTs have amount = 1
+                &ctx.accounts.nft_mint,
+                &ctx.accounts.auction.to_account_info(),
+                &ctx.accounts.nft_token_program,
+                Some(signer_seeds),
+            )
+            .map_err(|_| ErrorCode::FailedNftReturn)?;
+        }
+
+        // Step 2: Refund the highest bid (if any)
+        if ctx.accounts.auction.highest_bidder.is_some() {
+            let Some(bidder_payment_account) = ctx.accounts.bidder_payment_account.as_deref()
+            else {
+                return err!(ErrorCode::BidderMismatch);
+            };
+            transfer_tokens(
+                &ctx.accounts.payment_vault,
+                bidder_payment_account,
+                highest_bid,
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.auction.to_account_info(),
+                &ctx.accounts.payment_token_program,
+                Some(signer_seeds),
+            )
+            .map_err(|_| ErrorCode::FailedBidRefund)?;
+        }
+
+        if nft_available {
+            msg!("Auction settled: reserve not met, NFT returned to seller");
+        } else {
+            msg!("Auction settled: NFT vault empty or frozen, highest bid refunded");
+        }
+    }
+
+    // Step 3: Close both vaults (return rent to seller; a frozen NFT vault cannot be closed
+    // and a payment vault holding stray tokens is left open)
+    if !ctx.accounts.nft_vault.is_frozen() {
+        close_token_account(
+            &ctx.accounts.nft_vault,
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.auction.to_account_info(),
+            &ctx.accounts.nft_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedVaultClosure)?;
+    }
+    close_payment_vault(
+        &ctx.accounts.payment_vault,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.auction.to_account_info(),
+        &ctx.accounts.payment_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultClosure)?;
+
+    // Auction account automatically closes (close = seller constraint)
+    Ok(())
+}
\ No newline at end of file
@@ -1,7 +1,26 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::{
+    create, create_idempotent, get_associated_token_address_with_program_id, AssociatedToken,
+    Create,
+};
+use anchor_spl::metadata::{
+    mpl_token_metadata::accounts::{MasterEdition, Metadata},
+    MetadataAccount,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
 use anchor_spl::token_interface::{
-    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
-    TransferChecked,
+    approve_checked, close_account, revoke, transfer_checked, ApproveChecked, CloseAccount, Mint,
+    Revoke, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    error::ErrorCode,
+    state::{MarketplaceConfig, NftOffer},
 };
 
 /// Transfer tokens from one account to another
@@ -35,8 +54,27 @@ pub fn transfer_tokens<'info>(
     transfer_checked(cpi_context, amount, mint.decimals)
 }
 
+/// Transfer lamports from a signer to another account
+pub fn transfer_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let transfer_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+    };
+
+    transfer(
+        CpiContext::new(system_program.to_account_info(), transfer_accounts),
+        amount,
+    )
+}
+
 /// Close a token account and return rent to destination
-/// Supports both regular signers and PDA signers
+/// Supports both regular signers and PDA signers. Accounts that are already
+/// closed (Token Metadata closes the emptied source of a pNFT transfer) are skipped
 pub fn close_token_account<'info>(
     token_account: &InterfaceAccount<'info, TokenAccount>,
     destination: &AccountInfo<'info>,
@@ -44,6 +82,10 @@ pub fn close_token_account<'info>(
     token_program: &Interface<'info, TokenInterface>,
     signer_seeds: Option<&[&[&[u8]]]>,
 ) -> Result<()> {
+    if token_account.to_account_info().lamports() == 0 {
+        return Ok(());
+    }
+
     let close_accounts = CloseAccount {
         account: token_account.to_account_info(),
         destination: destination.to_account_info(),
@@ -57,4 +99,505 @@ pub fn close_token_account<'info>(
     };
 
     close_account(cpi_context)
+}
+
+/// Close a payment vault once this instruction has paid out what it escrowed
+/// Vaults are public ATAs that anyone can send dust to, so a vault that still
+/// holds tokens is left open instead of failing (and locking) the instruction
+pub fn close_payment_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<()> {
+    let info = vault.to_account_info();
+    if info.lamports() > 0 {
+        // Read the live balance; `vault.amount` predates this instruction's transfers
+        let leftover = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?.amount;
+        if leftover > 0 {
+            msg!("Vault {} left open holding {} stray tokens", vault.key(), leftover);
+            return Ok(());
+        }
+    }
+
+    close_token_account(vault, destination, authority, token_program, signer_seeds)
+}
+
+/// Approve `delegate` to move the single NFT held in `token_account`
+pub fn approve_nft_delegate<'info>(
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    delegate: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let approve_accounts = ApproveChecked {
+        to: token_account.to_account_info(),
+        mint: mint.to_account_info(),
+        delegate: delegate.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+
+    approve_checked(
+        CpiContext::new(token_program.to_account_info(), approve_accounts),
+        1, // NFTs have amount = 1
+        mint.decimals,
+    )
+}
+
+/// Remove the delegate of a token account (owner must sign)
+pub fn revoke_delegate<'info>(
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let revoke_accounts = Revoke {
+        source: token_account.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+
+    revoke(CpiContext::new(token_program.to_account_info(), revoke_accounts))
+}
+
+/// Check a delegate listing can still be filled: the maker's account holds the
+/// NFT and the offer PDA is still its delegate
+pub fn require_listing_delegate(
+    maker_nft_account: &TokenAccount,
+    offer: &Pubkey,
+) -> Result<()> {
+    require!(
+        maker_nft_account.amount == 1
+            && maker_nft_account.delegate == COption::Some(*offer)
+            && maker_nft_account.delegated_amount >= 1,
+        ErrorCode::DelegateRevoked
+    );
+    Ok(())
+}
+
+/// Check a mint is a true NFT: no decimals, a supply of exactly one and no
+/// mint authority other than its master edition. Token-2022 mints must not
+/// carry extensions that let anyone else move, lock or tax the NFT
+pub fn require_strict_nft(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    require!(mint.decimals == 0, ErrorCode::InvalidNftDecimals);
+    require!(mint.supply == 1, ErrorCode::InvalidNftSupply);
+
+    // Token Metadata hands the mint authority to the master edition
+    let (master_edition, _) = MasterEdition::find_pda(&mint.key());
+    require!(
+        match mint.mint_authority {
+            COption::None => true,
+            COption::Some(authority) => authority == master_edition,
+        },
+        ErrorCode::InvalidNftMintAuthority
+    );
+
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        match extension {
+            ExtensionType::PermanentDelegate => return err!(ErrorCode::PermanentDelegateMint),
+            ExtensionType::NonTransferable => return err!(ErrorCode::NonTransferableMint),
+            ExtensionType::TransferHook => return err!(ErrorCode::TransferHookMint),
+            ExtensionType::TransferFeeConfig => return err!(ErrorCode::TransferFeeMint),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check that a payment mint delivers the full amount of every transfer
+/// Escrowed bids are refunded and paid out at face value, so a Token-2022
+/// transfer fee would leave the vault short
+pub fn require_no_transfer_fee(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    require!(
+        !state.get_extension_types()?.contains(&ExtensionType::TransferFeeConfig),
+        ErrorCode::TransferFeePaymentMint
+    );
+    Ok(())
+}
+
+/// Check that an NFT's metadata marks it as a verified member of a collection
+pub fn require_verified_collection(
+    metadata: &MetadataAccount,
+    collection_mint: &Pubkey,
+) -> Result<()> {
+    match &metadata.collection {
+        Some(collection) if collection.verified && collection.key == *collection_mint => Ok(()),
+        _ => err!(ErrorCode::CollectionMismatch),
+    }
+}
+
+/// Collection a listing is restricted to: the one the maker named, the
+/// marketplace's, or either when both agree (an NFT has one collection)
+pub fn listing_collection(
+    requested: Option<Pubkey>,
+    config: &MarketplaceConfig,
+) -> Result<Option<Pubkey>> {
+    match (requested, config.collection) {
+        (Some(requested), Some(marketplace)) => {
+            require_keys_eq!(requested, marketplace, ErrorCode::CollectionMismatch);
+            Ok(Some(requested))
+        }
+        (requested, marketplace) => Ok(requested.or(marketplace)),
+    }
+}
+
+/// Check an NFT belongs to the collection its listing is restricted to (if any)
+/// The NFT's metadata must be passed whenever there is a restriction
+pub fn require_listing_collection(
+    metadata: Option<&Account<MetadataAccount>>,
+    collection: Option<&Pubkey>,
+) -> Result<()> {
+    let Some(collection_mint) = collection else {
+        return Ok(());
+    };
+    let Some(metadata) = metadata else {
+        return err!(ErrorCode::InvalidMetadata);
+    };
+    require_verified_collection(metadata, collection_mint)
+}
+
+/// Load an NFT's Token Metadata account (None when the NFT has none)
+pub fn load_metadata(metadata: &AccountInfo) -> Result<Option<MetadataAccount>> {
+    if metadata.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(
+        *metadata.owner,
+        anchor_spl::metadata::ID,
+        ErrorCode::InvalidMetadata
+    );
+    let metadata = MetadataAccount::try_deserialize(&mut &metadata.try_borrow_data()?[..])?;
+    Ok(Some(metadata))
+}
+
+/// Royalty owed to each verified creator of an NFT on a sale of `sale_amount`
+/// Returns an empty list when the NFT has no Token Metadata account;
+/// creators whose share rounds down to zero are left out
+pub fn creator_royalties(metadata: &AccountInfo, sale_amount: u64) -> Result<Vec<(Pubkey, u64)>> {
+    let Some(metadata) = load_metadata(metadata)? else {
+        return Ok(Vec::new());
+    };
+
+    // Total royalty pool, then each verified creator's percentage share of it
+    let royalty_pool = (sale_amount as u128)
+        .checked_mul(metadata.seller_fee_basis_points as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / 10_000;
+
+    let royalties = metadata
+        .creators
+        .iter()
+        .flatten()
+        .filter(|creator| creator.verified)
+        .map(|creator| (creator.address, (royalty_pool * creator.share as u128 / 100) as u64))
+        .filter(|(_, amount)| *amount > 0)
+        .collect();
+
+    Ok(royalties)
+}
+
+/// Load a creator's payment token account and check it is their ATA for `mint`
+pub fn creator_payment_account<'info>(
+    account: &'info AccountInfo<'info>,
+    creator: &Pubkey,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<InterfaceAccount<'info, TokenAccount>> {
+    let expected = get_associated_token_address_with_program_id(
+        creator,
+        &mint.key(),
+        &token_program.key(),
+    );
+    require_keys_eq!(account.key(), expected, ErrorCode::InvalidCreatorAccount);
+
+    InterfaceAccount::try_from(account)
+}
+
+/// Token accounts used when a sale is paid in SPL tokens
+pub struct TokenPayment<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub payer_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub maker_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub treasury_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+/// Accounts that move a sale's payment out of the payer
+pub struct SalePayment<'a, 'info> {
+    pub payer: AccountInfo<'info>,
+    pub maker: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+    pub system_program: &'a Program<'info, System>,
+    /// SPL token accounts, or None when paying in native SOL
+    pub token: Option<TokenPayment<'a, 'info>>,
+    /// PDA signer seeds when the payer is an escrow (SPL payments only)
+    pub signer_seeds: Option<&'a [&'a [&'a [u8]]]>,
+}
+
+impl<'info> SalePayment<'_, 'info> {
+    /// Pay `amount` to a wallet (native SOL) or to its token account
+    fn pay(
+        &self,
+        wallet: &AccountInfo<'info>,
+        token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<()> {
+        match (&self.token, token_account) {
+            (None, _) => transfer_lamports(&self.payer, wallet, amount, self.system_program),
+            (Some(token), Some(to)) => transfer_tokens(
+                token.payer_account,
+                to,
+                amount,
+                token.mint,
+                &self.payer,
+                token.token_program,
+                self.signer_seeds,
+            ),
+            (Some(_), None) => err!(ErrorCode::MissingPaymentAccount),
+        }
+    }
+}
+
+/// Split a sale between the marketplace fee, creator royalties and the maker
+/// Creator accounts are the creators' wallets for native SOL sales and their
+/// payment token accounts for SPL sales, in the order of `royalties`.
+/// Royalties are capped at what is left of the price after the fee.
+/// Returns (maker_amount, royalty_amount)
+pub fn settle_sale<'info>(
+    payment: &SalePayment<'_, 'info>,
+    price: u64,
+    fee_amount: u64,
+    royalties: &[(Pubkey, u64)],
+    creator_accounts: &'info [AccountInfo<'info>],
+) -> Result<(u64, u64)> {
+    require!(
+        creator_accounts.len() >= royalties.len(),
+        ErrorCode::MissingCreatorAccount
+    );
+
+    // Step 1: Marketplace fee to treasury
+    if fee_amount > 0 {
+        payment
+            .pay(
+                &payment.treasury,
+                payment.token.as_ref().map(|token| token.treasury_account),
+                fee_amount,
+            )
+            .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+    }
+
+    // Step 2: Royalties to each verified creator, out of what the fee left
+    let mut maker_amount = price
+        .checked_sub(fee_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let mut royalty_amount: u64 = 0;
+    for ((creator, amount), account) in royalties.iter().zip(creator_accounts) {
+        let amount = (*amount).min(maker_amount);
+        match &payment.token {
+            None => {
+                require_keys_eq!(account.key(), *creator, ErrorCode::InvalidCreatorAccount);
+                payment.pay(account, None, amount)
+            }
+            Some(token) => {
+                let creator_account =
+                    creator_payment_account(account, creator, token.mint, token.token_program)?;
+                payment.pay(account, Some(&creator_account), amount)
+            }
+        }
+        .map_err(|_| ErrorCode::FailedRoyaltyTransfer)?;
+        maker_amount -= amount;
+        royalty_amount += amount;
+    }
+
+    // Step 3: Remaining payment to maker
+    payment
+        .pay(
+            &payment.maker,
+            payment.token.as_ref().map(|token| token.maker_account),
+            maker_amount,
+        )
+        .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+
+    Ok((maker_amount, royalty_amount))
+}
+
+/// Accounts used to move the extra NFTs of a bundle listing in and out of escrow
+/// Every extra NFT uses the same token program as the offer's primary NFT.
+pub struct BundleTransfer<'a, 'info> {
+    /// The offer PDA owning the bundle vaults
+    pub offer: AccountInfo<'info>,
+    /// Pays rent for any token account that has to be created
+    pub payer: AccountInfo<'info>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub associated_token_program: &'a Program<'info, AssociatedToken>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> BundleTransfer<'_, 'info> {
+    /// Check `account` is `wallet`'s ATA for `mint`, creating it if `create_missing`
+    fn token_account(
+        &self,
+        account: &'info AccountInfo<'info>,
+        wallet: &AccountInfo<'info>,
+        mint: &InterfaceAccount<'info, Mint>,
+        create_missing: bool,
+    ) -> Result<InterfaceAccount<'info, TokenAccount>> {
+        let expected = get_associated_token_address_with_program_id(
+            &wallet.key(),
+            &mint.key(),
+            &self.token_program.key(),
+        );
+        require_keys_eq!(account.key(), expected, ErrorCode::InvalidBundleAccounts);
+
+        if create_missing {
+            let create_accounts = Create {
+                payer: self.payer.clone(),
+                associated_token: account.clone(),
+                authority: wallet.clone(),
+                mint: mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            };
+            create_idempotent(CpiContext::new(
+                self.associated_token_program.to_account_info(),
+                create_accounts,
+            ))?;
+        }
+
+        InterfaceAccount::try_from(account)
+    }
+
+    /// Load a bundle NFT mint and check it belongs to the NFT token program
+    fn mint(&self, account: &'info AccountInfo<'info>) -> Result<InterfaceAccount<'info, Mint>> {
+        require_keys_eq!(
+            *account.owner,
+            self.token_program.key(),
+            ErrorCode::InvalidBundleAccounts
+        );
+        InterfaceAccount::try_from(account)
+    }
+
+    /// Escrow each extra NFT from `owner` into a new vault owned by the offer
+    /// Accounts: (nft_mint, owner_nft_account, nft_vault) for each extra NFT,
+    /// followed by its metadata when `collection` restricts the listing.
+    /// Returns the escrowed mints in order
+    pub fn escrow(
+        &self,
+        accounts: &'info [AccountInfo<'info>],
+        owner: &AccountInfo<'info>,
+        collection: Option<&Pubkey>,
+    ) -> Result<Vec<Pubkey>> {
+        let stride = if collection.is_some() { 4 } else { 3 };
+        let bundle_size = accounts.len() / stride;
+        require!(
+            bundle_size * stride == accounts.len() && bundle_size <= NftOffer::MAX_BUNDLE_EXTRAS,
+            ErrorCode::InvalidBundleAccounts
+        );
+
+        let mut mints = Vec::with_capacity(bundle_size);
+        for chunk in accounts.chunks(stride) {
+            let mint = self.mint(&chunk[0])?;
+            require_strict_nft(&mint)?;
+            if let Some(collection) = collection {
+                let (expected_metadata, _) = Metadata::find_pda(&mint.key());
+                require_keys_eq!(chunk[3].key(), expected_metadata, ErrorCode::InvalidMetadata);
+                let metadata: Account<MetadataAccount> = Account::try_from(&chunk[3])?;
+                require_verified_collection(&metadata, collection)?;
+            }
+            let owner_account = self.token_account(&chunk[1], owner, &mint, false)?;
+            require!(owner_account.amount == 1, ErrorCode::FailedNftTransfer);
+
+            // A fresh vault per NFT (fails on a repeated mint)
+            let expected_vault = get_associated_token_address_with_program_id(
+                &self.offer.key(),
+                &mint.key(),
+                &self.token_program.key(),
+            );
+            require_keys_eq!(chunk[2].key(), expected_vault, ErrorCode::InvalidBundleAccounts);
+            create(CpiContext::new(
+                self.associated_token_program.to_account_info(),
+                Create {
+                    payer: self.payer.clone(),
+                    associated_token: chunk[2].clone(),
+                    authority: self.offer.clone(),
+                    mint: mint.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+            ))?;
+            let vault: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&chunk[2])?;
+
+            transfer_tokens(
+                &owner_account,
+                &vault,
+                1, // NFTs have amount = 1
+                &mint,
+                owner,
+                self.token_program,
+                None, // Owner is regular signer
+            )
+            .map_err(|_| ErrorCode::FailedNftTransfer)?;
+            mints.push(mint.key());
+        }
+
+        Ok(mints)
+    }
+
+    /// Send each extra NFT from its vault to `recipient` and close the vault
+    /// Accounts: (nft_mint, nft_vault, recipient_nft_account) for each mint in
+    /// `bundle_mints`, in listing order; missing recipient accounts are created
+    pub fn release(
+        &self,
+        accounts: &'info [AccountInfo<'info>],
+        bundle_mints: &[Pubkey],
+        recipient: &AccountInfo<'info>,
+        rent_destination: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        require!(
+            accounts.len() == bundle_mints.len() * 3,
+            ErrorCode::InvalidBundleAccounts
+        );
+
+        for (chunk, bundle_mint) in accounts.chunks(3).zip(bundle_mints) {
+            require_keys_eq!(chunk[0].key(), *bundle_mint, ErrorCode::InvalidBundleAccounts);
+            let mint = self.mint(&chunk[0])?;
+            let vault = self.token_account(&chunk[1], &self.offer, &mint, false)?;
+            let recipient_account = self.token_account(&chunk[2], recipient, &mint, true)?;
+            require!(vault.amount == 1, ErrorCode::EmptyVault);
+
+            transfer_tokens(
+                &vault,
+                &recipient_account,
+                1, // NFTs have amount = 1
+                &mint,
+                &self.offer,
+                self.token_program,
+                Some(signer_seeds),
+            )
+            .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
+            close_token_account(
+                &vault,
+                rent_destination,
+                &self.offer,
+                self.token_program,
+                Some(signer_seeds),
+            )
+            .map_err(|_| ErrorCode::FailedVaultClosure)?;
+        }
+
+        Ok(())
+    }
 }
\ No newline at end of file
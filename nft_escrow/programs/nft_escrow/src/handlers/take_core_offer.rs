@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::core_asset::{
+    core_royalties, require_core_collection, transfer_core_asset, CoreTransfer, MPL_CORE_ID,
+};
+use super::shared::{settle_sale, SalePayment, TokenPayment};
+use crate::{
+    error::ErrorCode,
+    state::{CoreOffer, MarketplaceConfig, PaymentMode},
+};
+
+#[derive(Accounts)]
+pub struct TakeCoreOffer<'info> {
+    /// Metaplex Core program (owns the asset)
+    /// CHECK: Address checked against the Core program ID
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts and native SOL payments
+    pub system_program: Program<'info, System>,
+
+    /// The user accepting the offer (pays tokens or SOL, receives the asset)
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// The original offer creator (receives payment)
+    /// CHECK: Validated via has_one constraint on offer account
+    #[account(mut)]
+    pub maker: UncheckedAccount<'info>,
+
+    /// The MPL Core asset being transferred
+    /// CHECK: Validated via has_one constraint on offer account
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+
+    /// Core collection of the asset (omit if the asset is not in a collection)
+    /// CHECK: Must match the collection saved on the offer; validated in the handler
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    /// The payment token mint (omit for native SOL offers)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Taker's payment token account (source of payment; omit for native SOL offers)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub taker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Maker's payment token account (receives payment; omit for native SOL offers)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee (directly, for native SOL offers)
+    /// CHECK: Validated via has_one constraint on config account
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives marketplace fee; omit for native SOL offers)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub treasury_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The offer account holding the asset (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = asset,
+        seeds = [
+            b"core_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, CoreOffer>,
+}
+
+/// Accept an MPL Core offer by paying tokens (or native SOL) to receive the asset
+/// Fails unless the offer still matches the price, mint and payment mode the
+/// taker saw (a wrapped SOL offer and a native SOL offer share the native mint).
+/// Remaining accounts: the payment destination of each royalty creator in the
+/// asset's Royalties plugin (or its collection's), in plugin order (payment
+/// token account for token offers, the creator's wallet for native SOL offers)
+pub fn take_core_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeCoreOffer<'info>>,
+    expected_token_amount: u64,
+    expected_payment_mint: Pubkey,
+    expected_payment_mode: PaymentMode,
+) -> Result<()> {
+    let price = ctx.accounts.offer.token_amount;
+
+    // Validate offer terms have not changed since the taker saw them
+    require!(
+        price == expected_token_amount,
+        ErrorCode::PriceMismatch
+    );
+    require_keys_eq!(
+        ctx.accounts.offer.payment_mint,
+        expected_payment_mint,
+        ErrorCode::PaymentMintMismatch
+    );
+    require!(
+        ctx.accounts.offer.payment_mode == expected_payment_mode,
+        ErrorCode::PaymentModeMismatch
+    );
+
+    // Validate taker is allowed to fill a private offer
+    require!(
+        ctx.accounts.offer.can_be_taken_by(&ctx.accounts.taker.key()),
+        ErrorCode::TakerNotAllowed
+    );
+
+    // Validate offer has not expired
+    require!(
+        !ctx.accounts.offer.is_expired(Clock::get()?.unix_timestamp),
+        ErrorCode::OfferExpired
+    );
+
+    // Validate the collection matches the one the asset was listed with
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+    require_core_collection(collection.as_ref(), ctx.accounts.offer.collection)?;
+
+    // Token offers need the payment mint and all payment token accounts
+    let token_payment = match ctx.accounts.offer.payment_mode {
+        PaymentMode::NativeSol => None,
+        PaymentMode::Token => {
+            let (Some(mint), Some(payer_account), Some(maker_account), Some(treasury_account)) = (
+                ctx.accounts.payment_mint.as_ref(),
+                ctx.accounts.taker_payment_account.as_deref(),
+                ctx.accounts.maker_payment_account.as_deref(),
+                ctx.accounts.treasury_payment_account.as_deref(),
+            ) else {
+                return err!(ErrorCode::MissingPaymentAccount);
+            };
+            require_keys_eq!(
+                mint.key(),
+                ctx.accounts.offer.payment_mint,
+                ErrorCode::PaymentMintMismatch
+            );
+            Some(TokenPayment {
+                mint,
+                payer_account,
+                maker_account,
+                treasury_account,
+                token_program: &ctx.accounts.payment_token_program,
+            })
+        }
+    };
+
+    // Royalties are read before the transfer (Core may rewrite the asset)
+    let royalties = core_royalties(&ctx.accounts.asset, collection.as_ref(), price)?;
+
+    // Prepare PDA signer seeds for the asset transfer
+    let offer_seeds = &[
+        b"core_offer",
+        ctx.accounts.offer.maker.as_ref(),
+        &ctx.accounts.offer.offer_id.to_le_bytes()[..],
+        &[ctx.accounts.offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Step 1: Transfer the asset from the offer PDA to taker
+    transfer_core_asset(
+        CoreTransfer {
+            asset: ctx.accounts.asset.to_account_info(),
+            collection,
+            payer: ctx.accounts.taker.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+            new_owner: ctx.accounts.taker.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            core_program: ctx.accounts.mpl_core_program.to_account_info(),
+        },
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
+
+    // Step 2: Pay marketplace fee, creator royalties and the maker
+    let fee_amount = ctx.accounts.config.fee_for(price)?;
+    let payment = SalePayment {
+        payer: ctx.accounts.taker.to_account_info(),
+        maker: ctx.accounts.maker.to_account_info(),
+        treasury: ctx.accounts.treasury.to_account_info(),
+        system_program: &ctx.accounts.system_program,
+        token: token_payment,
+        signer_seeds: None, // Taker is regular signer
+    };
+    let (maker_amount, royalty_amount) = settle_sale(
+        &payment,
+        price,
+        fee_amount,
+        &royalties,
+        ctx.remaining_accounts,
+    )?;
+
+    msg!(
+        "Core offer taken: asset transferred to taker, {} paid to maker, {} fee, {} royalties",
+        maker_amount,
+        fee_amount,
+        royalty_amount
+    );
+
+    // Offer account automatically closes (close = maker constraint)
+    Ok(())
+}
\ No newline at end of file
@@ -1,24 +1,38 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
+    metadata::Metadata,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use super::shared::{close_token_account, transfer_tokens};
-use crate::{error::ErrorCode, state::NftOffer};
+use super::pnft::*;
+use super::shared::{
+    close_token_account, creator_royalties, require_listing_delegate, settle_sale,
+    BundleTransfer, SalePayment, TokenPayment,
+};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, MarketplaceConfig, NftOffer, PaymentMode},
+};
 
 #[derive(Accounts)]
 pub struct TakeOffer<'info> {
     /// Program for managing associated token accounts
     pub associated_token_program: Program<'info, AssociatedToken>,
 
-    /// Token program (SPL Token or Token-2022)
-    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
 
-    /// System program for creating accounts
+    /// Metaplex Token Metadata program (owns the NFT metadata)
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// System program for creating accounts and native SOL payments
     pub system_program: Program<'info, System>,
 
-    /// The user accepting the offer (pays tokens, receives NFT)
+    /// The user accepting the offer (pays tokens or SOL, receives NFT)
     #[account(mut)]
     pub taker: Signer<'info>,
 
@@ -29,24 +43,37 @@ pub struct TakeOffer<'info> {
 
     /// The NFT mint being transferred
     #[account(
-        mint::token_program = token_program,
+        mint::token_program = nft_token_program,
     )]
     pub nft_mint: InterfaceAccount<'info, Mint>,
 
-    /// The payment token mint
+    /// Metadata of the NFT (royalty settings; empty if the NFT has none)
+    /// CHECK: Address derived from nft_mint; only read when initialized
     #[account(
-        mint::token_program = token_program,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = token_metadata_program.key(),
+        bump,
     )]
-    pub payment_mint: InterfaceAccount<'info, Mint>,
+    pub nft_metadata: UncheckedAccount<'info>,
 
-    /// Taker's payment token account (source of payment)
+    /// The payment token mint (omit for native SOL offers)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Taker's payment token account (source of payment; omit for native SOL offers)
     #[account(
         mut,
         associated_token::mint = payment_mint,
         associated_token::authority = taker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = payment_token_program,
     )]
-    pub taker_payment_account: InterfaceAccount<'info, TokenAccount>,
+    pub taker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// Taker's NFT account (receives the NFT)
     #[account(
@@ -54,19 +81,42 @@ pub struct TakeOffer<'info> {
         payer = taker,
         associated_token::mint = nft_mint,
         associated_token::authority = taker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = nft_token_program,
     )]
     pub taker_nft_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Maker's payment token account (receives payment)
+    /// Maker's payment token account (receives payment; omit for native SOL offers)
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = payment_mint,
         associated_token::authority = maker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee (directly, for native SOL offers)
+    /// CHECK: Validated via has_one constraint on config account
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives marketplace fee; omit for native SOL offers)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
     )]
-    pub maker_payment_account: InterfaceAccount<'info, TokenAccount>,
+    pub treasury_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// The offer account (will be closed)
     #[account(
@@ -74,75 +124,212 @@ pub struct TakeOffer<'info> {
         close = maker,
         has_one = maker,
         has_one = nft_mint,
-        has_one = payment_mint,
-        seeds = [b"nft_offer", offer.offer_id.to_le_bytes().as_ref()],
+        seeds = [
+            b"nft_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
         bump = offer.bump
     )]
     pub offer: Account<'info, NftOffer>,
 
-    /// Vault holding the escrowed NFT
+    /// Vault holding the escrowed NFT (omit for delegate listings)
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = offer,
-        associated_token::token_program = token_program,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Maker's NFT account (source of the NFT for delegate listings; omit otherwise)
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = nft_token_program,
     )]
-    pub nft_vault: InterfaceAccount<'info, TokenAccount>,
+    pub maker_nft_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Token Metadata accounts of a programmable NFT (omit for regular NFTs)
+    pub pnft: PnftAccounts<'info>,
 }
 
-/// Accept an NFT offer by paying tokens to receive the NFT
-pub fn take_offer(ctx: Context<TakeOffer>) -> Result<()> {
-    // Validate vault has the NFT
+/// Accept an NFT offer by paying tokens (or native SOL) to receive the NFT
+/// Dutch auction listings charge their current price from the Clock sysvar.
+/// Fails unless the offer still matches the price, mint and payment mode the
+/// taker saw (a wrapped SOL offer and a native SOL offer share the native mint).
+/// Remaining accounts: for bundle listings, nft_mint, nft_vault and
+/// taker_nft_account of each extra NFT in listing order; then the payment
+/// destination of each verified royalty creator of the primary NFT, in the
+/// order they appear in its metadata (payment token account for token offers,
+/// the creator's wallet for native SOL offers)
+pub fn take_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeOffer<'info>>,
+    expected_token_amount: u64,
+    expected_payment_mint: Pubkey,
+    expected_payment_mode: PaymentMode,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let price = ctx.accounts.offer.current_price(now);
+
+    // Validate offer terms have not changed since the taker saw them
+    require!(
+        ctx.accounts.offer.accepts_price(price, expected_token_amount),
+        ErrorCode::PriceMismatch
+    );
+    require_keys_eq!(
+        ctx.accounts.offer.payment_mint,
+        expected_payment_mint,
+        ErrorCode::PaymentMintMismatch
+    );
+    require!(
+        ctx.accounts.offer.payment_mode == expected_payment_mode,
+        ErrorCode::PaymentModeMismatch
+    );
+
+    // Validate taker is allowed to fill a private offer
+    require!(
+        ctx.accounts.offer.can_be_taken_by(&ctx.accounts.taker.key()),
+        ErrorCode::TakerNotAllowed
+    );
+
+    // Validate offer has not expired
     require!(
-        ctx.accounts.nft_vault.amount == 1,
-        ErrorCode::EmptyVault
+        !ctx.accounts.offer.is_expired(now),
+        ErrorCode::OfferExpired
     );
 
+    // Validate the NFT is still in the vault, or still delegated to the offer
+    let nft_source = match ctx.accounts.offer.custody {
+        CustodyMode::Vault => {
+            let Some(nft_vault) = ctx.accounts.nft_vault.as_deref() else {
+                return err!(ErrorCode::InvalidCustodyAccounts);
+            };
+            require!(nft_vault.amount == 1, ErrorCode::EmptyVault);
+            nft_vault
+        }
+        CustodyMode::Delegate => {
+            let Some(maker_nft_account) = ctx.accounts.maker_nft_account.as_deref() else {
+                return err!(ErrorCode::InvalidCustodyAccounts);
+            };
+            require_listing_delegate(maker_nft_account, &ctx.accounts.offer.key())?;
+            maker_nft_account
+        }
+    };
+
+    // Token offers need the payment mint and all payment token accounts
+    let token_payment = match ctx.accounts.offer.payment_mode {
+        PaymentMode::NativeSol => None,
+        PaymentMode::Token => {
+            let (Some(mint), Some(payer_account), Some(maker_account), Some(treasury_account)) = (
+                ctx.accounts.payment_mint.as_ref(),
+                ctx.accounts.taker_payment_account.as_deref(),
+                ctx.accounts.maker_payment_account.as_deref(),
+                ctx.accounts.treasury_payment_account.as_deref(),
+            ) else {
+                return err!(ErrorCode::MissingPaymentAccount);
+            };
+            require_keys_eq!(
+                mint.key(),
+                ctx.accounts.offer.payment_mint,
+                ErrorCode::PaymentMintMismatch
+            );
+            Some(TokenPayment {
+                mint,
+                payer_account,
+                maker_account,
+                treasury_account,
+                token_program: &ctx.accounts.payment_token_program,
+            })
+        }
+    };
+
     // Prepare PDA signer seeds for vault operations
     let offer_seeds = &[
         b"nft_offer",
+        ctx.accounts.offer.maker.as_ref(),
         &ctx.accounts.offer.offer_id.to_le_bytes()[..],
         &[ctx.accounts.offer.bump],
     ];
     let signer_seeds = &[&offer_seeds[..]];
 
-    // Step 1: Transfer NFT from vault to taker
-    transfer_tokens(
-        &ctx.accounts.nft_vault,
-        &ctx.accounts.taker_nft_account,
-        1, // NFTs have amount = 1
-        &ctx.accounts.nft_mint,
-        &ctx.accounts.offer.to_account_info(),
-        &ctx.accounts.token_program,
+    // Step 1: Transfer NFT to taker (from the vault, or as the maker's delegate)
+    transfer_nft(
+        NftTransfer {
+            from: nft_source,
+            from_owner: ctx.accounts.offer.to_account_info(),
+            to: &ctx.accounts.taker_nft_account,
+            to_owner: ctx.accounts.taker.to_account_info(),
+            mint: &ctx.accounts.nft_mint,
+            payer: ctx.accounts.taker.to_account_info(),
+            token_program: &ctx.accounts.nft_token_program,
+            associated_token_program: &ctx.accounts.associated_token_program,
+            system_program: &ctx.accounts.system_program,
+            pnft: &ctx.accounts.pnft,
+        },
         Some(signer_seeds),
     )
     .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
 
     // Step 2: Close the NFT vault (return rent to taker)
-    close_token_account(
-        &ctx.accounts.nft_vault,
+    if let Some(nft_vault) = ctx.accounts.nft_vault.as_deref() {
+        close_token_account(
+            nft_vault,
+            &ctx.accounts.taker.to_account_info(),
+            &ctx.accounts.offer.to_account_info(),
+            &ctx.accounts.nft_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedVaultClosure)?;
+    }
+
+    // Step 3: Transfer the extra NFTs of a bundle listing to taker
+    let bundle_len = ctx.accounts.offer.bundle_mints.len() * 3;
+    require!(
+        ctx.remaining_accounts.len() >= bundle_len,
+        ErrorCode::InvalidBundleAccounts
+    );
+    let (bundle_accounts, creator_accounts) = ctx.remaining_accounts.split_at(bundle_len);
+    let bundle = BundleTransfer {
+        offer: ctx.accounts.offer.to_account_info(),
+        payer: ctx.accounts.taker.to_account_info(),
+        token_program: &ctx.accounts.nft_token_program,
+        associated_token_program: &ctx.accounts.associated_token_program,
+        system_program: &ctx.accounts.system_program,
+    };
+    bundle.release(
+        bundle_accounts,
+        &ctx.accounts.offer.bundle_mints,
         &ctx.accounts.taker.to_account_info(),
-        &ctx.accounts.offer.to_account_info(),
-        &ctx.accounts.token_program,
-        Some(signer_seeds),
-    )
-    .map_err(|_| ErrorCode::FailedVaultClosure)?;
-
-    // Step 3: Transfer payment tokens from taker to maker
-    transfer_tokens(
-        &ctx.accounts.taker_payment_account,
-        &ctx.accounts.maker_payment_account,
-        ctx.accounts.offer.token_amount,
-        &ctx.accounts.payment_mint,
         &ctx.accounts.taker.to_account_info(),
-        &ctx.accounts.token_program,
-        None, // Taker is regular signer
-    )
-    .map_err(|_| ErrorCode::InsufficientPaymentBalance)?;
+        signer_seeds,
+    )?;
+
+    // Step 4: Pay marketplace fee, creator royalties and the maker
+    let fee_amount = ctx.accounts.config.fee_for(price)?;
+    let royalties = creator_royalties(&ctx.accounts.nft_metadata, price)?;
+    let payment = SalePayment {
+        payer: ctx.accounts.taker.to_account_info(),
+        maker: ctx.accounts.maker.to_account_info(),
+        treasury: ctx.accounts.treasury.to_account_info(),
+        system_program: &ctx.accounts.system_program,
+        token: token_payment,
+        signer_seeds: None, // Taker is regular signer
+    };
+    let (maker_amount, royalty_amount) = settle_sale(
+        &payment,
+        price,
+        fee_amount,
+        &royalties,
+        creator_accounts,
+    )?;
 
     msg!(
-        "Offer taken: NFT transferred to taker, {} tokens paid to maker",
-        ctx.accounts.offer.token_amount
+        "Offer taken: NFTs transferred to taker, {} paid to maker, {} fee, {} royalties",
+        maker_amount,
+        fee_amount,
+        royalty_amount
     );
 
     // Offer account automatically closes (close = maker constraint)
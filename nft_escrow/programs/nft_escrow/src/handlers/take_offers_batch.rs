@@ -0,0 +1,411 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    metadata::mpl_token_metadata::accounts::Metadata as MetadataPda,
+    token::spl_token::native_mint,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    close_token_account, creator_royalties, require_listing_delegate, settle_sale,
+    transfer_tokens, SalePayment, TokenPayment,
+};
+use crate::{
+    error::ErrorCode,
+    state::{CustodyMode, MarketplaceConfig, NftOffer, PaymentMode},
+};
+
+/// Accounts passed for each offer before its royalty creator accounts:
+/// offer, maker, nft_mint, nft_source, nft_metadata, taker_nft_account, maker_payment_account
+const OFFER_ACCOUNTS: usize = 7;
+
+/// Terms the taker agreed to for one offer of a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BatchFill {
+    /// Price the taker saw (the most they will pay for Dutch auctions)
+    pub expected_token_amount: u64,
+    /// Number of royalty creator accounts following this offer's accounts
+    pub creator_count: u8,
+}
+
+#[derive(Accounts)]
+pub struct TakeOffersBatch<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of every NFT mint in the batch (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// System program for creating accounts and native SOL payments
+    pub system_program: Program<'info, System>,
+
+    /// The user sweeping the offers (pays tokens or SOL, receives NFTs)
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// The payment token mint shared by every offer (omit for native SOL offers)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Taker's payment token account (source of payment; omit for native SOL offers)
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub taker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee (directly, for native SOL offers)
+    /// CHECK: Validated via has_one constraint on config account
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives marketplace fee; omit for native SOL offers)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub treasury_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+/// An offer of the batch that passed every check
+struct FillableOffer<'info> {
+    offer: Account<'info, NftOffer>,
+    nft_mint: InterfaceAccount<'info, Mint>,
+    /// The offer's NFT vault, or the maker's NFT account for delegate listings
+    nft_source: InterfaceAccount<'info, TokenAccount>,
+    price: u64,
+}
+
+/// Take several NFT offers in one transaction
+/// All offers must share the payment mint (or native SOL) and NFT token
+/// program; bundle listings are not supported. Offers are filled in order
+/// until the total spend would exceed `max_total_spend`. With
+/// `all_or_nothing` any offer that cannot be filled fails the whole batch,
+/// otherwise offers that were taken, expired, repriced or no longer fit the
+/// budget are skipped (malformed accounts still fail the batch).
+/// Remaining accounts: for each entry of `fills`, the offer's accounts
+/// (offer, maker, nft_mint, nft_vault - the maker's NFT account for
+/// delegate listings, nft_metadata, taker_nft_account, maker_payment_account -
+/// any account for native SOL offers) followed by
+/// its `creator_count` royalty creator accounts (see `take_offer`)
+pub fn take_offers_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeOffersBatch<'info>>,
+    fills: Vec<BatchFill>,
+    max_total_spend: u64,
+    all_or_nothing: bool,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = &*ctx.accounts;
+    let mut remaining_accounts = ctx.remaining_accounts;
+    let mut total_spent: u64 = 0;
+    let mut filled: usize = 0;
+
+    for (index, fill) in fills.iter().enumerate() {
+        // Split off this offer's accounts
+        let group_len = OFFER_ACCOUNTS + fill.creator_count as usize;
+        require!(
+            remaining_accounts.len() >= group_len,
+            ErrorCode::InvalidBatchAccounts
+        );
+        let (group, rest) = remaining_accounts.split_at(group_len);
+        remaining_accounts = rest;
+
+        // Check the offer can still be filled within the spend cap
+        let budget = max_total_spend - total_spent;
+        let fillable = match check_fill(accounts, group, fill, now, budget) {
+            Ok(fillable) => fillable,
+            Err(error) if !all_or_nothing && is_skippable(&error) => {
+                msg!("Offer {} skipped: {}", index, error);
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+
+        let price = fillable.price;
+        fill_offer(accounts, group, fillable)?;
+        total_spent = total_spent.checked_add(price).ok_or(ErrorCode::MathOverflow)?;
+        filled += 1;
+    }
+
+    // Validate no unexpected accounts were passed
+    require!(
+        remaining_accounts.is_empty(),
+        ErrorCode::InvalidBatchAccounts
+    );
+
+    msg!(
+        "Batch taken: {} of {} offers filled, {} spent",
+        filled,
+        fills.len(),
+        total_spent
+    );
+    Ok(())
+}
+
+/// Whether a best-effort batch may skip an offer that failed `check_fill`
+/// Only the offer's state can change under the taker (taken, expired,
+/// repriced, over budget or its NFT moved); malformed accounts always fail
+fn is_skippable(error: &Error) -> bool {
+    [
+        ErrorCode::OfferClosed,
+        ErrorCode::OfferExpired,
+        ErrorCode::PriceMismatch,
+        ErrorCode::SpendCapExceeded,
+        ErrorCode::EmptyVault,
+        ErrorCode::DelegateRevoked,
+        ErrorCode::FrozenNftAccount,
+    ]
+    .into_iter()
+    .any(|code| *error == code.into())
+}
+
+/// Load an offer of the batch and check it can be filled for at most `budget`
+fn check_fill<'info>(
+    accounts: &TakeOffersBatch<'info>,
+    group: &'info [AccountInfo<'info>],
+    fill: &BatchFill,
+    now: i64,
+    budget: u64,
+) -> Result<FillableOffer<'info>> {
+    // Offers that were already taken or cancelled have been closed
+    require!(!group[0].data_is_empty(), ErrorCode::OfferClosed);
+    let offer: Account<NftOffer> = Account::try_from(&group[0])?;
+    require_keys_eq!(group[1].key(), offer.maker, ErrorCode::InvalidBatchAccounts);
+    require_keys_eq!(group[2].key(), offer.nft_mint, ErrorCode::InvalidBatchAccounts);
+    require!(
+        offer.bundle_mints.is_empty(),
+        ErrorCode::InvalidBundleAccounts
+    );
+
+    // Validate offer terms match what the taker agreed to
+    let price = offer.current_price(now);
+    require!(
+        offer.accepts_price(price, fill.expected_token_amount),
+        ErrorCode::PriceMismatch
+    );
+    // No payment mint means the taker pays in native SOL
+    let (payment_mint, payment_mode) = match &accounts.payment_mint {
+        Some(mint) => (mint.key(), PaymentMode::Token),
+        None => (native_mint::ID, PaymentMode::NativeSol),
+    };
+    require_keys_eq!(
+        offer.payment_mint,
+        payment_mint,
+        ErrorCode::PaymentMintMismatch
+    );
+    require!(
+        offer.payment_mode == payment_mode,
+        ErrorCode::PaymentModeMismatch
+    );
+    require!(
+        offer.can_be_taken_by(&accounts.taker.key()),
+        ErrorCode::TakerNotAllowed
+    );
+    require!(!offer.is_expired(now), ErrorCode::OfferExpired);
+    require!(price <= budget, ErrorCode::SpendCapExceeded);
+
+    // Validate the NFT is still in the vault, or still delegated to the offer
+    require_keys_eq!(
+        *group[2].owner,
+        accounts.nft_token_program.key(),
+        ErrorCode::InvalidBatchAccounts
+    );
+    let nft_mint: InterfaceAccount<Mint> = InterfaceAccount::try_from(&group[2])?;
+    let nft_source = match offer.custody {
+        CustodyMode::Vault => {
+            let nft_vault = associated_account(
+                &group[3],
+                &offer.key(),
+                &nft_mint,
+                &accounts.nft_token_program,
+            )?;
+            require!(nft_vault.amount == 1, ErrorCode::EmptyVault);
+            nft_vault
+        }
+        CustodyMode::Delegate => {
+            let maker_nft_account = associated_account(
+                &group[3],
+                &offer.maker,
+                &nft_mint,
+                &accounts.nft_token_program,
+            )?;
+            require_listing_delegate(&maker_nft_account, &offer.key())?;
+            maker_nft_account
+        }
+    };
+
+    // Frozen accounts (pNFTs, which need take_offer) cannot be moved by a plain transfer
+    require!(!nft_source.is_frozen(), ErrorCode::FrozenNftAccount);
+
+    Ok(FillableOffer {
+        offer,
+        nft_mint,
+        nft_source,
+        price,
+    })
+}
+
+/// Deliver one offer's NFT to the taker, pay the sale and close the offer
+fn fill_offer<'info>(
+    accounts: &TakeOffersBatch<'info>,
+    group: &'info [AccountInfo<'info>],
+    fillable: FillableOffer<'info>,
+) -> Result<()> {
+    let FillableOffer {
+        offer,
+        nft_mint,
+        nft_source,
+        price,
+    } = fillable;
+    let maker = &group[1];
+
+    // Prepare PDA signer seeds for vault operations
+    let offer_seeds = &[
+        b"nft_offer",
+        offer.maker.as_ref(),
+        &offer.offer_id.to_le_bytes()[..],
+        &[offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Step 1: Transfer NFT to taker (from the vault, or as the maker's delegate)
+    let taker_nft_account = taker_funded_account(
+        accounts,
+        &group[5],
+        &accounts.taker.to_account_info(),
+        &nft_mint,
+        &accounts.nft_token_program,
+    )?;
+    transfer_tokens(
+        &nft_source,
+        &taker_nft_account,
+        1, // NFTs have amount = 1
+        &nft_mint,
+        &offer.to_account_info(),
+        &accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
+
+    // Step 2: Close the NFT vault (return rent to taker)
+    if offer.custody == CustodyMode::Vault {
+        close_token_account(
+            &nft_source,
+            &accounts.taker.to_account_info(),
+            &offer.to_account_info(),
+            &accounts.nft_token_program,
+            Some(signer_seeds),
+        )
+        .map_err(|_| ErrorCode::FailedVaultClosure)?;
+    }
+
+    // Step 3: Pay marketplace fee, creator royalties and the maker
+    let maker_payment_account = match &accounts.payment_mint {
+        Some(mint) => Some(taker_funded_account(
+            accounts,
+            &group[6],
+            maker,
+            mint,
+            &accounts.payment_token_program,
+        )?),
+        None => None,
+    };
+    let token_payment = match (
+        accounts.payment_mint.as_ref(),
+        accounts.taker_payment_account.as_deref(),
+        maker_payment_account.as_ref(),
+        accounts.treasury_payment_account.as_deref(),
+    ) {
+        (Some(mint), Some(payer_account), Some(maker_account), Some(treasury_account)) => {
+            Some(TokenPayment {
+                mint,
+                payer_account,
+                maker_account,
+                treasury_account,
+                token_program: &accounts.payment_token_program,
+            })
+        }
+        (None, ..) => None,
+        _ => return err!(ErrorCode::MissingPaymentAccount),
+    };
+
+    let (metadata, _) = MetadataPda::find_pda(&nft_mint.key());
+    require_keys_eq!(group[4].key(), metadata, ErrorCode::InvalidMetadata);
+    let fee_amount = accounts.config.fee_for(price)?;
+    let royalties = creator_royalties(&group[4], price)?;
+    let payment = SalePayment {
+        payer: accounts.taker.to_account_info(),
+        maker: maker.clone(),
+        treasury: accounts.treasury.to_account_info(),
+        system_program: &accounts.system_program,
+        token: token_payment,
+        signer_seeds: None, // Taker is regular signer
+    };
+    settle_sale(
+        &payment,
+        price,
+        fee_amount,
+        &royalties,
+        &group[OFFER_ACCOUNTS..],
+    )?;
+
+    // Step 4: Close the offer account (return rent to maker)
+    offer.close(maker.clone())
+}
+
+/// Check `account` is `wallet`'s associated token account for `mint` and load it
+fn associated_account<'info>(
+    account: &'info AccountInfo<'info>,
+    wallet: &Pubkey,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<InterfaceAccount<'info, TokenAccount>> {
+    let expected =
+        get_associated_token_address_with_program_id(wallet, &mint.key(), &token_program.key());
+    require_keys_eq!(account.key(), expected, ErrorCode::InvalidBatchAccounts);
+
+    InterfaceAccount::try_from(account)
+}
+
+/// Load `wallet`'s associated token account for `mint`, creating it (taker pays) if missing
+fn taker_funded_account<'info>(
+    accounts: &TakeOffersBatch<'info>,
+    account: &'info AccountInfo<'info>,
+    wallet: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<InterfaceAccount<'info, TokenAccount>> {
+    create_idempotent(CpiContext::new(
+        accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: accounts.taker.to_account_info(),
+            associated_token: account.clone(),
+            authority: wallet.clone(),
+            mint: mint.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))?;
+
+    associated_account(account, &wallet.key(), mint, token_program)
+}
\ No newline at end of file
@@ -0,0 +1,371 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use super::shared::{
+    close_payment_vault, close_token_account, creator_royalties, load_metadata,
+    require_strict_nft, require_verified_collection, settle_sale, transfer_tokens, SalePayment,
+    TokenPayment,
+};
+use crate::{
+    error::ErrorCode,
+    state::{MarketplaceConfig, SwapOffer, SwapRequest, SwapTopUp, TopUpDirection},
+};
+
+#[derive(Accounts)]
+pub struct TakeSwapOffer<'info> {
+    /// Program for managing associated token accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program of the offered NFT mint (SPL Token or Token-2022)
+    pub nft_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the requested NFT mint (SPL Token or Token-2022)
+    pub requested_token_program: Interface<'info, TokenInterface>,
+
+    /// Token program of the payment mint (SPL Token or Token-2022)
+    pub payment_token_program: Interface<'info, TokenInterface>,
+
+    /// Metaplex Token Metadata program (owns the NFT metadata)
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// System program for creating accounts
+    pub system_program: Program<'info, System>,
+
+    /// The user accepting the swap (sends the requested NFT, receives the offered NFT)
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// The original offer creator (receives the requested NFT)
+    /// CHECK: Validated via has_one constraint on swap offer account
+    #[account(mut)]
+    pub maker: UncheckedAccount<'info>,
+
+    /// The NFT mint being offered
+    #[account(
+        mint::token_program = nft_token_program,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Metadata of the offered NFT (royalties on a taker-paid top-up; empty if the NFT has none)
+    /// CHECK: Address derived from nft_mint; only read when initialized
+    #[account(
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    /// The NFT mint the taker sends in exchange
+    #[account(
+        mint::token_program = requested_token_program,
+    )]
+    pub requested_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Metadata of the requested NFT (proves collection membership for collection swaps and
+    /// sets royalties on a maker-paid top-up; empty if the NFT has none)
+    /// CHECK: Address derived from requested_mint; only read when initialized
+    #[account(
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            requested_mint.key().as_ref(),
+        ],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub requested_metadata: UncheckedAccount<'info>,
+
+    /// The token mint of the top-up (omit for swaps without a top-up)
+    #[account(
+        mint::token_program = payment_token_program,
+    )]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Taker's account for the requested NFT (must have exactly 1 NFT)
+    #[account(
+        mut,
+        associated_token::mint = requested_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = requested_token_program,
+    )]
+    pub taker_requested_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Maker's account for the requested NFT (receives it)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = requested_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = requested_token_program,
+    )]
+    pub maker_requested_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker's NFT account (receives the offered NFT)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = nft_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub taker_nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Taker's payment token account (pays or receives the top-up)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = taker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub taker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Maker's payment token account (receives a taker-paid top-up)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = maker,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub maker_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Marketplace settings (fee and treasury)
+    #[account(
+        has_one = treasury,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, MarketplaceConfig>>,
+
+    /// Wallet receiving the marketplace fee
+    /// CHECK: Validated via has_one constraint on config account
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Treasury's payment token account (receives the marketplace fee on a top-up)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub treasury_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// The swap offer account (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = nft_mint,
+        seeds = [
+            b"swap_offer",
+            maker.key().as_ref(),
+            swap_offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = swap_offer.bump
+    )]
+    pub swap_offer: Box<Account<'info, SwapOffer>>,
+
+    /// Vault holding the escrowed NFT
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = nft_token_program,
+    )]
+    pub nft_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault holding a maker-paid top-up
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = swap_offer,
+        associated_token::token_program = payment_token_program,
+    )]
+    pub top_up_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+/// Accept a swap offer by sending the requested NFT in exchange for the escrowed one
+/// Any token top-up moves in the direction the maker chose and is settled like a
+/// sale of the NFT its payer receives: marketplace fee, that NFT's creator
+/// royalties, then the rest to the other side.
+/// Remaining accounts: payment token account of each verified royalty creator of
+/// that NFT, in the order they appear in its metadata
+pub fn take_swap_offer<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TakeSwapOffer<'info>>,
+) -> Result<()> {
+    // Validate offer has not expired
+    require!(
+        !ctx.accounts.swap_offer.is_expired(Clock::get()?.unix_timestamp),
+        ErrorCode::OfferExpired
+    );
+
+    // Validate vault has the NFT
+    require!(
+        ctx.accounts.nft_vault.amount == 1,
+        ErrorCode::EmptyVault
+    );
+
+    // Validate the taker's NFT is the one the maker asked for
+    match ctx.accounts.swap_offer.requested {
+        SwapRequest::Mint(mint) => require_keys_eq!(
+            ctx.accounts.requested_mint.key(),
+            mint,
+            ErrorCode::RequestedNftMismatch
+        ),
+        SwapRequest::Collection(collection_mint) => {
+            let Some(metadata) = load_metadata(&ctx.accounts.requested_metadata)? else {
+                return err!(ErrorCode::InvalidMetadata);
+            };
+            require_verified_collection(&metadata, &collection_mint)?;
+        }
+    }
+
+    // Validate taker has exactly 1 of the requested NFT and it is a real single-edition mint
+    require_strict_nft(&ctx.accounts.requested_mint)?;
+    require!(
+        ctx.accounts.taker_requested_account.amount == 1,
+        ErrorCode::RequestedNftMismatch
+    );
+
+    // Prepare PDA signer seeds for vault operations
+    let offer_seeds = &[
+        b"swap_offer",
+        ctx.accounts.swap_offer.maker.as_ref(),
+        &ctx.accounts.swap_offer.offer_id.to_le_bytes()[..],
+        &[ctx.accounts.swap_offer.bump],
+    ];
+    let signer_seeds = &[&offer_seeds[..]];
+
+    // Step 1: Transfer requested NFT from taker to maker
+    transfer_tokens(
+        &ctx.accounts.taker_requested_account,
+        &ctx.accounts.maker_requested_account,
+        1, // NFTs have amount = 1
+        &ctx.accounts.requested_mint,
+        &ctx.accounts.taker.to_account_info(),
+        &ctx.accounts.requested_token_program,
+        None, // Taker is regular signer
+    )
+    .map_err(|_| ErrorCode::FailedNftTransfer)?;
+
+    // Step 2: Transfer offered NFT from vault to taker
+    transfer_tokens(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.taker_nft_account,
+        1, // NFTs have amount = 1
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.swap_offer.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultNftTransfer)?;
+
+    // Step 3: Close the NFT vault (return rent to taker)
+    close_token_account(
+        &ctx.accounts.nft_vault,
+        &ctx.accounts.taker.to_account_info(),
+        &ctx.accounts.swap_offer.to_account_info(),
+        &ctx.accounts.nft_token_program,
+        Some(signer_seeds),
+    )
+    .map_err(|_| ErrorCode::FailedVaultClosure)?;
+
+    // Step 4: Settle the token top-up (fee, royalties, then the receiving side)
+    if let Some(SwapTopUp { amount, direction }) = ctx.accounts.swap_offer.top_up {
+        let (Some(mint), Some(taker_payment_account), Some(treasury_account)) = (
+            ctx.accounts.payment_mint.as_deref(),
+            ctx.accounts.taker_payment_account.as_deref(),
+            ctx.accounts.treasury_payment_account.as_deref(),
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccount);
+        };
+        require!(
+            ctx.accounts.swap_offer.payment_mint == Some(mint.key()),
+            ErrorCode::PaymentMintMismatch
+        );
+
+        // The payer buys the NFT it receives: the maker pays for the requested NFT
+        // out of the top-up vault, the taker pays for the offered NFT directly
+        let (payer, payer_account, recipient, recipient_account, metadata, payer_seeds) =
+            match direction {
+                TopUpDirection::MakerPays => (
+                    ctx.accounts.swap_offer.to_account_info(),
+                    ctx.accounts.top_up_vault.as_deref(),
+                    ctx.accounts.taker.to_account_info(),
+                    Some(taker_payment_account),
+                    &ctx.accounts.requested_metadata,
+                    Some(&signer_seeds[..]),
+                ),
+                TopUpDirection::TakerPays => (
+                    ctx.accounts.taker.to_account_info(),
+                    Some(taker_payment_account),
+                    ctx.accounts.maker.to_account_info(),
+                    ctx.accounts.maker_payment_account.as_deref(),
+                    &ctx.accounts.nft_metadata,
+                    None, // Taker is regular signer
+                ),
+            };
+        let (Some(payer_account), Some(recipient_account)) = (payer_account, recipient_account)
+        else {
+            return err!(ErrorCode::MissingPaymentAccount);
+        };
+
+        let fee_amount = ctx.accounts.config.fee_for(amount)?;
+        let royalties = creator_royalties(metadata, amount)?;
+        let payment = SalePayment {
+            payer,
+            maker: recipient,
+            treasury: ctx.accounts.treasury.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token: Some(TokenPayment {
+                mint,
+                payer_account,
+                maker_account: recipient_account,
+                treasury_account,
+                token_program: &ctx.accounts.payment_token_program,
+            }),
+            signer_seeds: payer_seeds,
+        };
+        let (recipient_amount, royalty_amount) = settle_sale(
+            &payment,
+            amount,
+            fee_amount,
+            &royalties,
+            ctx.remaining_accounts,
+        )?;
+
+        // Step 5: Close an emptied top-up vault (return rent to maker)
+        if direction == TopUpDirection::MakerPays {
+            close_payment_vault(
+                payer_account,
+                &ctx.accounts.maker.to_account_info(),
+                &ctx.accounts.swap_offer.to_account_info(),
+                &ctx.accounts.payment_token_program,
+                Some(signer_seeds),
+            )
+            .map_err(|_| ErrorCode::FailedVaultClosure)?;
+        }
+
+        msg!(
+            "Swap top-up settled: {} paid to the other side, {} fee, {} royalties",
+            recipient_amount,
+            fee_amount,
+            royalty_amount
+        );
+    }
+
+    msg!("Swap offer taken: NFTs exchanged");
+
+    // Swap offer account automatically closes (close = maker constraint)
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, state::MarketplaceConfig};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The current config admin (only they can update)
+    pub admin: Signer<'info>,
+
+    /// The marketplace config account
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"marketplace_config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, MarketplaceConfig>,
+}
+
+/// Update the marketplace fee, treasury and/or admin (`None` keeps the current value)
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    fee_bps: Option<u16>,
+    treasury: Option<Pubkey>,
+    admin: Option<Pubkey>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if let Some(fee_bps) = fee_bps {
+        // Validate fee does not exceed the 10% cap (MAX_FEE_BPS)
+        require!(
+            fee_bps <= MarketplaceConfig::MAX_FEE_BPS,
+            ErrorCode::InvalidFeeBps
+        );
+        config.fee_bps = fee_bps;
+    }
+
+    if let Some(treasury) = treasury {
+        config.treasury = treasury;
+    }
+
+    if let Some(admin) = admin {
+        config.admin = admin;
+    }
+
+    msg!(
+        "Marketplace config updated: Fee={}bps, Treasury={}, Admin={}",
+        config.fee_bps,
+        config.treasury,
+        config.admin
+    );
+    Ok(())
+}
\ No newline at end of file
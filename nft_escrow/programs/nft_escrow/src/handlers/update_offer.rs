@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{Mint, TokenInterface},
+};
+
+use crate::{
+    error::ErrorCode,
+    state::{NftOffer, PaymentMode},
+};
+
+#[derive(Accounts)]
+pub struct UpdateOffer<'info> {
+    /// Token program of the new payment mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The original offer creator (only they can reprice)
+    pub maker: Signer<'info>,
+
+    /// New token mint the maker wants in payment (omit to keep the current one, or
+    /// when switching to native SOL)
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub new_payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The offer account being repriced (NFT stays in its vault)
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [
+            b"nft_offer",
+            maker.key().as_ref(),
+            offer.offer_id.to_le_bytes().as_ref(),
+        ],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, NftOffer>,
+}
+
+/// Change the price (and optionally the payment mint and mode) of an open offer
+/// Token mode keeps the current mint unless a new one is passed; native SOL mode
+/// takes no mint.
+pub fn update_offer(
+    ctx: Context<UpdateOffer>,
+    token_amount: u64,
+    payment_mode: PaymentMode,
+) -> Result<()> {
+    // Validate token amount is greater than zero
+    require!(token_amount > 0, ErrorCode::InvalidTokenAmount);
+
+    // Validate offer is fixed price (Dutch auctions follow their schedule)
+    require!(
+        ctx.accounts.offer.dutch_auction.is_none(),
+        ErrorCode::DutchAuctionNotEditable
+    );
+
+    // Resolve the payment mint for the requested mode
+    let payment_mint = match (payment_mode, &ctx.accounts.new_payment_mint) {
+        (PaymentMode::Token, Some(new_payment_mint)) => new_payment_mint.key(),
+        (PaymentMode::Token, None) => {
+            // Switching from native SOL to tokens needs a mint
+            require!(
+                ctx.accounts.offer.payment_mode == PaymentMode::Token,
+                ErrorCode::MissingPaymentAccount
+            );
+            ctx.accounts.offer.payment_mint
+        }
+        (PaymentMode::NativeSol, None) => native_mint::ID,
+        (PaymentMode::NativeSol, Some(_)) => return err!(ErrorCode::PaymentModeMismatch),
+    };
+
+    let offer = &mut ctx.accounts.offer;
+    offer.token_amount = token_amount;
+    offer.payment_mint = payment_mint;
+    offer.payment_mode = payment_mode;
+
+    msg!(
+        "Offer updated: ID={}, Payment={}tokens of {}",
+        offer.offer_id,
+        token_amount,
+        offer.payment_mint
+    );
+    Ok(())
+}
\ No newline at end of file
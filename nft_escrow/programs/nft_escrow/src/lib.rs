@@ -2,6 +2,7 @@
 
 use anchor_lang::prelude::*;
 use handlers::*;
+use state::{DutchAuction, PaymentMode, SwapRequest, SwapTopUp};
 
 pub mod error;
 pub mod handlers;
@@ -13,22 +14,225 @@ declare_id!("9QAQvv6ritDHHTcctxYnPdBgPXb7G3bZeTWkZYdg1moJ");
 pub mod nft_escrow {
     use super::*;
 
-    /// Create an NFT offer - escrow an NFT in exchange for tokens
-    pub fn make_offer(
-        ctx: Context<MakeOffer>,
-        offer_id: u64,
+    /// Create an NFT offer - escrow an NFT (or a bundle of NFTs) in exchange for tokens,
+    /// optionally restricted to a verified collection
+    pub fn make_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
         token_amount: u64,
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+        collection: Option<Pubkey>,
     ) -> Result<()> {
-        handlers::make_offer::make_offer(ctx, offer_id, token_amount)
+        handlers::make_offer::make_offer(ctx, token_amount, expires_at, allowed_taker, collection)
+    }
+
+    /// Create a Dutch auction offer - escrow an NFT at a price that falls over time
+    pub fn make_dutch_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
+        auction: DutchAuction,
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+        collection: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::make_dutch_offer::make_dutch_offer(
+            ctx,
+            auction,
+            expires_at,
+            allowed_taker,
+            collection,
+        )
+    }
+
+    /// Create a delegate offer - list an NFT without moving it out of the maker's wallet
+    pub fn make_delegate_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeOffer<'info>>,
+        token_amount: u64,
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+        collection: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::make_delegate_offer::make_delegate_offer(
+            ctx,
+            token_amount,
+            expires_at,
+            allowed_taker,
+            collection,
+        )
     }
 
     /// Accept an NFT offer - pay tokens to receive the NFT
-    pub fn take_offer(ctx: Context<TakeOffer>) -> Result<()> {
-        handlers::take_offer::take_offer(ctx)
+    pub fn take_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeOffer<'info>>,
+        expected_token_amount: u64,
+        expected_payment_mint: Pubkey,
+        expected_payment_mode: PaymentMode,
+    ) -> Result<()> {
+        handlers::take_offer::take_offer(
+            ctx,
+            expected_token_amount,
+            expected_payment_mint,
+            expected_payment_mode,
+        )
+    }
+
+    /// Take several NFT offers - sweep listings up to a total spend cap
+    pub fn take_offers_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeOffersBatch<'info>>,
+        fills: Vec<BatchFill>,
+        max_total_spend: u64,
+        all_or_nothing: bool,
+    ) -> Result<()> {
+        handlers::take_offers_batch::take_offers_batch(ctx, fills, max_total_spend, all_or_nothing)
     }
 
     /// Cancel an NFT offer - return the NFT to maker
-    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+    pub fn cancel_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelOffer<'info>>,
+    ) -> Result<()> {
         handlers::cancel_offer::cancel_offer(ctx)
     }
+
+    /// Update an NFT offer - maker reprices without moving the NFT
+    pub fn update_offer(
+        ctx: Context<UpdateOffer>,
+        token_amount: u64,
+        payment_mode: PaymentMode,
+    ) -> Result<()> {
+        handlers::update_offer::update_offer(ctx, token_amount, payment_mode)
+    }
+
+    /// Close an expired NFT offer - anyone can return the NFT to maker
+    pub fn close_expired_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseExpiredOffer<'info>>,
+    ) -> Result<()> {
+        handlers::close_expired_offer::close_expired_offer(ctx)
+    }
+
+    /// Place a collection bid - escrow tokens for any NFT from a verified collection
+    pub fn make_bid(ctx: Context<MakeBid>, token_amount: u64) -> Result<()> {
+        handlers::make_bid::make_bid(ctx, token_amount)
+    }
+
+    /// Accept a collection bid - sell an NFT from the collection for the escrowed tokens
+    pub fn accept_bid<'info>(ctx: Context<'_, '_, 'info, 'info, AcceptBid<'info>>) -> Result<()> {
+        handlers::accept_bid::accept_bid(ctx)
+    }
+
+    /// Cancel a collection bid - refund the escrowed tokens to the buyer
+    pub fn cancel_bid(ctx: Context<CancelBid>) -> Result<()> {
+        handlers::cancel_bid::cancel_bid(ctx)
+    }
+
+    /// Create an English auction - escrow an NFT and accept rising token bids
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        reserve_price: u64,
+        min_bid_increment: u64,
+        end_time: i64,
+        extension_window: i64,
+    ) -> Result<()> {
+        handlers::create_auction::create_auction(
+            ctx,
+            reserve_price,
+            min_bid_increment,
+            end_time,
+            extension_window,
+        )
+    }
+
+    /// Place an auction bid - escrow tokens and refund the previous highest bidder
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        handlers::place_bid::place_bid(ctx, amount)
+    }
+
+    /// Settle an ended auction - deliver NFT and pay seller, or return NFT if reserve unmet
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+    ) -> Result<()> {
+        handlers::settle_auction::settle_auction(ctx)
+    }
+
+    /// Cancel an auction with no bids - return the NFT to the seller
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+        handlers::cancel_auction::cancel_auction(ctx)
+    }
+
+    /// Create a swap offer - escrow an NFT in exchange for another NFT (plus optional tokens)
+    pub fn make_swap_offer(
+        ctx: Context<MakeSwapOffer>,
+        requested: SwapRequest,
+        top_up: Option<SwapTopUp>,
+        expires_at: i64,
+    ) -> Result<()> {
+        handlers::make_swap_offer::make_swap_offer(ctx, requested, top_up, expires_at)
+    }
+
+    /// Accept a swap offer - send the requested NFT and receive the escrowed one
+    pub fn take_swap_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeSwapOffer<'info>>,
+    ) -> Result<()> {
+        handlers::take_swap_offer::take_swap_offer(ctx)
+    }
+
+    /// Cancel a swap offer - return the NFT and any escrowed top-up to the maker
+    pub fn cancel_swap_offer(ctx: Context<CancelSwapOffer>) -> Result<()> {
+        handlers::cancel_swap_offer::cancel_swap_offer(ctx)
+    }
+
+    /// Create a Core offer - escrow an MPL Core asset in exchange for tokens
+    pub fn make_core_offer(
+        ctx: Context<MakeCoreOffer>,
+        token_amount: u64,
+        expires_at: i64,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::make_core_offer::make_core_offer(ctx, token_amount, expires_at, allowed_taker)
+    }
+
+    /// Accept a Core offer - pay tokens (or SOL) to receive the escrowed MPL Core asset
+    pub fn take_core_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeCoreOffer<'info>>,
+        expected_token_amount: u64,
+        expected_payment_mint: Pubkey,
+        expected_payment_mode: PaymentMode,
+    ) -> Result<()> {
+        handlers::take_core_offer::take_core_offer(
+            ctx,
+            expected_token_amount,
+            expected_payment_mint,
+            expected_payment_mode,
+        )
+    }
+
+    /// Cancel a Core offer - return the MPL Core asset to the maker
+    pub fn cancel_core_offer(ctx: Context<CancelCoreOffer>) -> Result<()> {
+        handlers::cancel_core_offer::cancel_core_offer(ctx)
+    }
+
+    /// Create the marketplace config - upgrade authority sets fee and treasury
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        handlers::initialize_config::initialize_config(ctx, fee_bps, treasury)
+    }
+
+    /// Update the marketplace config - admin changes fee, treasury or admin
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: Option<u16>,
+        treasury: Option<Pubkey>,
+        admin: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::update_config::update_config(ctx, fee_bps, treasury, admin)
+    }
+
+    /// Set the marketplace collection - admin restricts listings to a verified collection
+    pub fn set_config_collection(
+        ctx: Context<UpdateConfig>,
+        collection: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::set_config_collection::set_config_collection(ctx, collection)
+    }
 }
\ No newline at end of file
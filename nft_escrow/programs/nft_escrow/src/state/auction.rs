@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Stores details of an English auction with escrowed NFT and bids
+#[account]
+#[derive(InitSpace)]
+pub struct Auction {
+    /// Auction ID, unique per seller (allocated from their MakerProfile)
+    pub auction_id: u64,
+    
+    /// Public key of the user who created the auction
+    pub seller: Pubkey,
+    
+    /// The NFT mint being auctioned
+    pub nft_mint: Pubkey,
+    
+    /// The token mint bids are paid in
+    pub payment_mint: Pubkey,
+    
+    /// Lowest winning bid - below it the NFT returns to the seller
+    pub reserve_price: u64,
+    
+    /// Amount each new bid must exceed the highest bid by
+    pub min_bid_increment: u64,
+    
+    /// Unix timestamp after which no more bids are accepted
+    pub end_time: i64,
+    
+    /// Bids within this many seconds of the end push the end back (anti-sniping)
+    pub extension_window: i64,
+    
+    /// Highest bid so far (escrowed in the payment vault)
+    pub highest_bid: u64,
+    
+    /// Wallet that placed the highest bid (None = no bids yet)
+    pub highest_bidder: Option<Pubkey>,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
+
+impl Auction {
+    /// Longest an auction may run from creation (30 days)
+    pub const MAX_DURATION: i64 = 30 * 24 * 60 * 60;
+
+    /// Longest anti-sniping extension window (1 hour)
+    pub const MAX_EXTENSION_WINDOW: i64 = 60 * 60;
+
+    /// Whether bidding has closed at the given unix timestamp
+    pub fn has_ended(&self, now: i64) -> bool {
+        now >= self.end_time
+    }
+
+    /// Smallest bid that would currently be accepted
+    pub fn min_next_bid(&self) -> Result<u64> {
+        match self.highest_bidder {
+            Some(_) => self
+                .highest_bid
+                .checked_add(self.min_bid_increment)
+                .ok_or(ErrorCode::MathOverflow.into()),
+            None => Ok(1),
+        }
+    }
+
+    /// Whether the highest bid wins the NFT
+    pub fn reserve_met(&self) -> bool {
+        self.highest_bidder.is_some() && self.highest_bid >= self.reserve_price
+    }
+
+    /// Push the end time back if a bid lands inside the extension window
+    pub fn extend_for_bid(&mut self, now: i64) -> Result<()> {
+        let remaining = self
+            .end_time
+            .checked_sub(now)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if remaining < self.extension_window {
+            self.end_time = now
+                .checked_add(self.extension_window)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+/// Stores details of a collection-wide bid
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionBid {
+    /// Bid ID, unique per buyer (allocated from their MakerProfile)
+    pub bid_id: u64,
+    
+    /// Public key of the user who placed the bid
+    pub buyer: Pubkey,
+    
+    /// Verified collection mint - any NFT from it can fill the bid
+    pub collection_mint: Pubkey,
+    
+    /// The token mint escrowed as payment
+    pub payment_mint: Pubkey,
+    
+    /// Amount of payment tokens escrowed in the bid vault
+    pub token_amount: u64,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use super::PaymentMode;
+
+/// Stores details of an offer selling an MPL Core asset for tokens
+/// The asset is held by the offer PDA itself until the offer is taken.
+#[account]
+#[derive(InitSpace)]
+pub struct CoreOffer {
+    /// Offer ID, unique per maker (allocated from their MakerProfile)
+    pub offer_id: u64,
+    
+    /// Public key of the user who created the offer
+    pub maker: Pubkey,
+    
+    /// The MPL Core asset being offered
+    pub asset: Pubkey,
+    
+    /// Core collection the asset belongs to (None = not in a collection)
+    pub collection: Option<Pubkey>,
+    
+    /// The token mint that the maker wants in exchange
+    /// (the native mint for native SOL offers)
+    pub payment_mint: Pubkey,
+    
+    /// Whether the taker pays in SPL tokens or native SOL
+    pub payment_mode: PaymentMode,
+    
+    /// Amount of payment tokens required to take the offer
+    pub token_amount: u64,
+    
+    /// Unix timestamp after which the offer can no longer be taken
+    pub expires_at: i64,
+    
+    /// Only this wallet may take the offer (None = open to anyone)
+    pub allowed_taker: Option<Pubkey>,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
+
+impl CoreOffer {
+    /// Whether the offer has expired at the given unix timestamp
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Whether the given wallet is allowed to take the offer
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        match self.allowed_taker {
+            Some(allowed) => allowed == *taker,
+            None => true,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+/// Per-maker counter that allocates offer, auction and bid IDs on chain
+#[account]
+#[derive(InitSpace)]
+pub struct MakerProfile {
+    /// Public key of the maker this profile belongs to
+    pub maker: Pubkey,
+    
+    /// Number of listings (offers and auctions) and bids created so far (the next ID)
+    pub offer_count: u64,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Singleton settings for the marketplace running on this program
+#[account]
+#[derive(InitSpace)]
+pub struct MarketplaceConfig {
+    /// Authority allowed to update the config
+    pub admin: Pubkey,
+    
+    /// Marketplace fee taken from each sale, in basis points
+    pub fee_bps: u16,
+    
+    /// Wallet that receives marketplace fees
+    pub treasury: Pubkey,
+    
+    /// Collection every listing must be a verified member of (None = any NFT)
+    pub collection: Option<Pubkey>,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
+
+impl MarketplaceConfig {
+    /// Basis points in 100%
+    pub const BPS_DENOMINATOR: u16 = 10_000;
+
+    /// Highest marketplace fee (10%), leaving room for creator royalties
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    /// Marketplace fee owed on a sale of `amount` payment tokens
+    pub fn fee_for(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            / Self::BPS_DENOMINATOR as u128;
+        Ok(fee as u64)
+    }
+}
\ No newline at end of file
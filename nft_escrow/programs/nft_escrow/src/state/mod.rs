@@ -1,3 +1,15 @@
 pub mod nft_offer;
+pub mod collection_bid;
+pub mod marketplace_config;
+pub mod maker_profile;
+pub mod auction;
+pub mod swap_offer;
+pub mod core_offer;
 
-pub use nft_offer::*;
\ No newline at end of file
+pub use nft_offer::*;
+pub use collection_bid::*;
+pub use marketplace_config::*;
+pub use maker_profile::*;
+pub use auction::*;
+pub use swap_offer::*;
+pub use core_offer::*;
\ No newline at end of file
@@ -1,10 +1,51 @@
 use anchor_lang::prelude::*;
 
-/// Stores details of an NFT-for-token offer
+/// How the taker pays for an offer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PaymentMode {
+    /// SPL tokens of `payment_mint`
+    Token,
+    /// Native lamports
+    NativeSol,
+}
+
+/// Where a listed NFT is held until the offer is taken
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CustodyMode {
+    /// Escrowed in the offer's NFT vault
+    Vault,
+    /// Left in the maker's wallet with the offer PDA approved as delegate
+    Delegate,
+}
+
+/// Price schedule of a Dutch auction listing (price falls linearly over time)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct DutchAuction {
+    /// Price at `start_time`
+    pub start_price: u64,
+    /// Price from `start_time + duration` onwards
+    pub end_price: u64,
+    /// Unix timestamp when the price starts falling
+    pub start_time: i64,
+    /// Seconds over which the price falls from start to end
+    pub duration: i64,
+}
+
+impl DutchAuction {
+    /// Price of the listing at the given unix timestamp
+    pub fn price_at(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_time).clamp(0, self.duration);
+        let decay = (self.start_price - self.end_price) as u128 * elapsed as u128
+            / self.duration as u128;
+        self.start_price - decay as u64
+    }
+}
+
+/// Stores details of an NFT-for-token offer (a single NFT or a bundle)
 #[account]
 #[derive(InitSpace)]
 pub struct NftOffer {
-    /// Unique identifier for this offer
+    /// Offer ID, unique per maker (allocated from their MakerProfile)
     pub offer_id: u64,
     
     /// Public key of the user who created the offer
@@ -13,12 +54,72 @@ pub struct NftOffer {
     /// The NFT mint being offered
     pub nft_mint: Pubkey,
     
+    /// Extra NFT mints sold together with `nft_mint` as one bundle (empty = single NFT)
+    #[max_len(4)]
+    pub bundle_mints: Vec<Pubkey>,
+    
+    /// Whether the NFT is escrowed in a vault or delegated from the maker's wallet
+    pub custody: CustodyMode,
+    
     /// The token mint that the maker wants in exchange
+    /// (the native mint for native SOL offers)
     pub payment_mint: Pubkey,
     
+    /// Whether the taker pays in SPL tokens or native SOL
+    pub payment_mode: PaymentMode,
+    
     /// Amount of payment tokens required to take the offer
+    /// (the start price for Dutch auction listings)
     pub token_amount: u64,
     
+    /// Price schedule when listed as a Dutch auction (None = fixed price)
+    pub dutch_auction: Option<DutchAuction>,
+    
+    /// Unix timestamp after which the offer can no longer be taken
+    pub expires_at: i64,
+    
+    /// Only this wallet may take the offer (None = open to anyone)
+    pub allowed_taker: Option<Pubkey>,
+    
+    /// Verified collection the NFTs were checked against when listed
+    /// (None = unrestricted)
+    pub collection: Option<Pubkey>,
+    
     /// PDA bump seed (stored for efficiency)
     pub bump: u8,
+}
+
+impl NftOffer {
+    /// Most extra NFTs a bundle listing can hold besides `nft_mint`
+    pub const MAX_BUNDLE_EXTRAS: usize = 4;
+
+    /// Whether the offer has expired at the given unix timestamp
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Price a taker pays at the given unix timestamp
+    pub fn current_price(&self, now: i64) -> u64 {
+        match &self.dutch_auction {
+            Some(auction) => auction.price_at(now),
+            None => self.token_amount,
+        }
+    }
+
+    /// Whether a taker expecting `expected_price` accepts the current `price`
+    /// (for Dutch auctions the expected price is the most the taker will pay)
+    pub fn accepts_price(&self, price: u64, expected_price: u64) -> bool {
+        match self.dutch_auction {
+            Some(_) => price <= expected_price,
+            None => price == expected_price,
+        }
+    }
+
+    /// Whether the given wallet is allowed to take the offer
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        match self.allowed_taker {
+            Some(allowed) => allowed == *taker,
+            None => true,
+        }
+    }
 }
\ No newline at end of file
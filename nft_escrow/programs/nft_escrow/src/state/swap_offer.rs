@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+/// The NFT a swap offer asks for in return
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SwapRequest {
+    /// This exact NFT mint
+    Mint(Pubkey),
+    /// Any NFT verified as part of this collection
+    Collection(Pubkey),
+}
+
+/// Which side of a swap adds tokens to the trade
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TopUpDirection {
+    /// Maker escrows the top-up when listing; the taker receives it
+    MakerPays,
+    /// Taker pays the top-up to the maker when taking the swap
+    TakerPays,
+}
+
+/// Tokens added on top of an NFT-for-NFT swap
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct SwapTopUp {
+    /// Amount of payment tokens
+    pub amount: u64,
+    /// Who pays it
+    pub direction: TopUpDirection,
+}
+
+/// Stores details of an NFT-for-NFT swap offer
+#[account]
+#[derive(InitSpace)]
+pub struct SwapOffer {
+    /// Offer ID, unique per maker (allocated from their MakerProfile)
+    pub offer_id: u64,
+    
+    /// Public key of the user who created the offer
+    pub maker: Pubkey,
+    
+    /// The NFT mint being offered (escrowed in the NFT vault)
+    pub nft_mint: Pubkey,
+    
+    /// The NFT the maker wants in exchange
+    pub requested: SwapRequest,
+    
+    /// The token mint of the top-up (None = plain NFT-for-NFT swap)
+    pub payment_mint: Option<Pubkey>,
+    
+    /// Token top-up paid alongside the swap (None = no top-up)
+    pub top_up: Option<SwapTopUp>,
+    
+    /// Unix timestamp after which the offer can no longer be taken
+    pub expires_at: i64,
+    
+    /// PDA bump seed (stored for efficiency)
+    pub bump: u8,
+}
+
+impl SwapOffer {
+    /// Whether the offer has expired at the given unix timestamp
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Top-up amount the maker escrowed when listing (0 if none)
+    pub fn escrowed_top_up(&self) -> u64 {
+        match self.top_up {
+            Some(SwapTopUp {
+                amount,
+                direction: TopUpDirection::MakerPays,
+            }) => amount,
+            _ => 0,
+        }
+    }
+}
\ No newline at end of file
@@ -5,20 +5,49 @@ import {
   PublicKey, 
   Keypair, 
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   LAMPORTS_PER_SOL,
+  Transaction,
+  TransactionInstruction,
+  sendAndConfirmTransaction,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  NATIVE_MINT,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createMint,
   createAccount,
   mintTo,
   getAccount,
   getAssociatedTokenAddress,
+  getAssociatedTokenAddressSync,
   createAssociatedTokenAccount,
+  freezeAccount,
+  setAuthority,
+  AuthorityType,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeNonTransferableMintInstruction,
+  createInitializeTransferHookInstruction,
 } from "@solana/spl-token";
 import { assert } from "chai";
 
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bWW6b5Mp1s"
+);
+const MPL_CORE_PROGRAM_ID = new PublicKey(
+  "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+);
+const TOKEN_AUTH_RULES_PROGRAM_ID = new PublicKey(
+  "auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg"
+);
+
 describe("NFT Escrow Tests", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
@@ -42,23 +71,50 @@ describe("NFT Escrow Tests", () => {
   let takerPaymentAccount: PublicKey;
   
   // Program accounts
+  let makerProfile: PublicKey;
+  let offerId: anchor.BN;
   let offerAccount: PublicKey;
   let nftVault: PublicKey;
+
+  // Marketplace accounts
+  let treasury: Keypair;
+  let configAccount: PublicKey;
+  let treasuryPaymentAccount: PublicKey;
   
   // Test parameters
-  const offerId = new anchor.BN(1);
   const paymentAmount = new anchor.BN(100 * 1_000_000); // 100 tokens with 6 decimals
+  const feeBps = 250; // 2.5% marketplace fee
+  const feeAmount = (Number(paymentAmount) * feeBps) / 10_000;
+
+  // pNFT accounts, all omitted for regular NFTs
+  const noPnft = {
+    tokenMetadataProgram: null,
+    metadata: null,
+    edition: null,
+    ownerTokenRecord: null,
+    destinationTokenRecord: null,
+    sysvarInstructions: null,
+    authorizationRulesProgram: null,
+    authorizationRules: null,
+  };
 
   /**
    * Helper: Create an NFT (mint with supply 1, decimals 0)
    */
-  async function createNFT(owner: PublicKey): Promise<PublicKey> {
+  async function createNFT(
+    owner: PublicKey,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+    freezeAuthority: PublicKey | null = null
+  ): Promise<PublicKey> {
     const mint = await createMint(
       provider.connection,
       maker, // Payer
       mintAuthority.publicKey, // Mint authority
-      null, // Freeze authority
-      0 // Decimals = 0 for NFT
+      freezeAuthority, // Freeze authority
+      0, // Decimals = 0 for NFT
+      undefined,
+      undefined,
+      tokenProgram
     );
 
     // Create token account for owner
@@ -66,7 +122,10 @@ describe("NFT Escrow Tests", () => {
       provider.connection,
       maker,
       mint,
-      owner
+      owner,
+      undefined,
+      undefined,
+      tokenProgram
     );
 
     // Mint 1 NFT to owner
@@ -76,7 +135,23 @@ describe("NFT Escrow Tests", () => {
       mint,
       tokenAccount,
       mintAuthority,
-      1 // Amount = 1 for NFT
+      1, // Amount = 1 for NFT
+      [],
+      undefined,
+      tokenProgram
+    );
+
+    // Revoke the mint authority so the supply stays at 1
+    await setAuthority(
+      provider.connection,
+      maker,
+      mint,
+      mintAuthority,
+      AuthorityType.MintTokens,
+      null,
+      [],
+      undefined,
+      tokenProgram
     );
 
     return mint;
@@ -85,18 +160,447 @@ describe("NFT Escrow Tests", () => {
   /**
    * Helper: Create a fungible token mint
    */
-  async function createToken(): Promise<PublicKey> {
+  async function createToken(
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ): Promise<PublicKey> {
     const mint = await createMint(
       provider.connection,
       maker,
       mintAuthority.publicKey,
       null,
-      6 // 6 decimals (like USDC)
+      6, // 6 decimals (like USDC)
+      undefined,
+      undefined,
+      tokenProgram
     );
 
     return mint;
   }
 
+  /**
+   * Helper: Create an MPL Core asset (no collection, no plugins) with Core's
+   * CreateV1 instruction
+   */
+  async function createCoreAsset(owner: PublicKey): Promise<PublicKey> {
+    const asset = Keypair.generate();
+    const data = Buffer.concat([
+      Buffer.from([0, 0]), // CreateV1, account state
+      borshString("Core Asset"),
+      borshString("https://example.com/asset.json"),
+      Buffer.from([0]), // No plugins
+    ]);
+
+    // Core takes its own program ID in place of omitted optional accounts
+    const instruction = new TransactionInstruction({
+      programId: MPL_CORE_PROGRAM_ID,
+      keys: [
+        { pubkey: asset.publicKey, isSigner: true, isWritable: true },
+        { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+        { pubkey: owner, isSigner: false, isWritable: false },
+        { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: MPL_CORE_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+      data,
+    });
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(instruction),
+      [maker, asset]
+    );
+
+    return asset.publicKey;
+  }
+
+  /**
+   * Helper: Read the owner of an MPL Core asset
+   */
+  async function coreAssetOwner(asset: PublicKey): Promise<PublicKey> {
+    const info = await provider.connection.getAccountInfo(asset);
+    return new PublicKey(info.data.subarray(1, 33));
+  }
+
+  /**
+   * Helper: Derive the Token Metadata PDA of a mint
+   */
+  function findMetadataPda(mint: PublicKey): PublicKey {
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    return metadata;
+  }
+
+  /**
+   * Helper: Derive the Token Metadata master edition PDA of a mint
+   */
+  function findMasterEditionPda(mint: PublicKey): PublicKey {
+    const [edition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    return edition;
+  }
+
+  /**
+   * Helper: Derive the Token Metadata token record PDA of a pNFT token account
+   */
+  function findTokenRecordPda(
+    mint: PublicKey,
+    tokenAccount: PublicKey
+  ): PublicKey {
+    const [tokenRecord] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("token_record"),
+        tokenAccount.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    return tokenRecord;
+  }
+
+  /**
+   * Helper: Borsh-encode a string (u32 length prefix)
+   */
+  function borshString(value: string): Buffer {
+    const length = Buffer.alloc(4);
+    length.writeUInt32LE(value.length);
+    return Buffer.concat([length, Buffer.from(value)]);
+  }
+
+  /**
+   * Helper: MessagePack-encode the small values a rule set is made of
+   * (unsigned integers below 256, strings, arrays and string-keyed maps)
+   */
+  function msgpack(value: number | string | any[] | object): Buffer {
+    if (typeof value === "number") {
+      return value < 128 ? Buffer.from([value]) : Buffer.from([0xcc, value]);
+    }
+    if (typeof value === "string") {
+      return Buffer.concat([Buffer.from([0xd9, value.length]), Buffer.from(value)]);
+    }
+    if (Array.isArray(value)) {
+      const header = Buffer.from([0xdc, 0, 0]);
+      header.writeUInt16BE(value.length, 1);
+      return Buffer.concat([header, ...value.map(msgpack)]);
+    }
+    const keys = Object.keys(value);
+    return Buffer.concat([
+      Buffer.from([0x80 | keys.length]),
+      ...keys.map((key) => Buffer.concat([msgpack(key), msgpack(value[key])])),
+    ]);
+  }
+
+  /**
+   * Helper: Create a Token Authorization Rules rule set owned by the maker
+   * that lets every pNFT transfer pass
+   */
+  async function createRuleSet(name: string): Promise<PublicKey> {
+    const [ruleSet] = PublicKey.findProgramAddressSync(
+      [Buffer.from("rule_set"), maker.publicKey.toBuffer(), Buffer.from(name)],
+      TOKEN_AUTH_RULES_PROGRAM_ID
+    );
+    const operations = {};
+    for (const scenario of [
+      "Owner",
+      "TransferDelegate",
+      "SaleDelegate",
+      "WalletToWallet",
+    ]) {
+      operations[`Transfer:${scenario}`] = "Pass";
+    }
+    // RuleSetV1: lib version, owner, name, operation -> rule
+    const serializedRuleSet = msgpack([
+      1,
+      Array.from(maker.publicKey.toBytes()),
+      name,
+      operations,
+    ]);
+    const length = Buffer.alloc(4);
+    length.writeUInt32LE(serializedRuleSet.length);
+
+    const instruction = new TransactionInstruction({
+      programId: TOKEN_AUTH_RULES_PROGRAM_ID,
+      keys: [
+        { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+        { pubkey: ruleSet, isSigner: false, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.concat([
+        Buffer.from([0, 0]), // CreateOrUpdate, V1
+        length,
+        serializedRuleSet,
+      ]),
+    });
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(instruction),
+      [maker]
+    );
+
+    return ruleSet;
+  }
+
+  /**
+   * Helper: Create a Token Metadata NFT (with master edition) and mint it to
+   * the owner's associated token account with CreateV1 and MintV1; the maker
+   * is update authority. Pass a rule set to create a programmable NFT, a
+   * collection mint to add it (unverified) to that collection, and royalty
+   * settings with (unverified) creators and their percentage shares.
+   */
+  async function createMetadataNft(
+    owner: PublicKey,
+    options: {
+      ruleSet?: PublicKey;
+      collection?: PublicKey;
+      sellerFeeBasisPoints?: number;
+      creators?: { address: PublicKey; share: number }[];
+    } = {}
+  ): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    const metadata = findMetadataPda(mint.publicKey);
+    const edition = findMasterEditionPda(mint.publicKey);
+    const ownerNftAccount = await getAssociatedTokenAddress(
+      mint.publicKey,
+      owner
+    );
+    const programmable = options.ruleSet !== undefined;
+    const tokenRecord = programmable
+      ? findTokenRecordPda(mint.publicKey, ownerNftAccount)
+      : TOKEN_METADATA_PROGRAM_ID;
+    const ruleSetAccount = programmable
+      ? options.ruleSet
+      : TOKEN_METADATA_PROGRAM_ID;
+    const authRulesProgram = programmable
+      ? TOKEN_AUTH_RULES_PROGRAM_ID
+      : TOKEN_METADATA_PROGRAM_ID;
+
+    const createData = Buffer.concat([
+      Buffer.from([42, 0]), // CreateV1
+      borshString("Metadata NFT"),
+      borshString("MNFT"),
+      borshString("https://example.com/nft.json"),
+      new anchor.BN(options.sellerFeeBasisPoints ?? 0).toArrayLike(
+        Buffer,
+        "le",
+        2
+      ),
+      creatorsData(options.creators),
+      Buffer.from([0, 1]), // Primary sale not happened, mutable
+      Buffer.from([programmable ? 4 : 0]), // (Programmable)NonFungible
+      options.collection
+        ? Buffer.concat([Buffer.from([1, 0]), options.collection.toBuffer()])
+        : Buffer.from([0]), // Unverified collection
+      Buffer.from([0, 0]), // No uses or collection details
+      programmable
+        ? Buffer.concat([Buffer.from([1]), options.ruleSet.toBuffer()])
+        : Buffer.from([0]),
+      Buffer.from([1, 0]), // Decimals = 0
+      Buffer.from([1, 0]), // Print supply = zero
+    ]);
+    const create = new TransactionInstruction({
+      programId: TOKEN_METADATA_PROGRAM_ID,
+      keys: [
+        { pubkey: metadata, isSigner: false, isWritable: true },
+        { pubkey: edition, isSigner: false, isWritable: true },
+        { pubkey: mint.publicKey, isSigner: true, isWritable: true },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+      data: createData,
+    });
+
+    const mintNft = new TransactionInstruction({
+      programId: TOKEN_METADATA_PROGRAM_ID,
+      keys: [
+        { pubkey: ownerNftAccount, isSigner: false, isWritable: true },
+        { pubkey: owner, isSigner: false, isWritable: false },
+        { pubkey: metadata, isSigner: false, isWritable: false },
+        { pubkey: edition, isSigner: false, isWritable: true },
+        { pubkey: tokenRecord, isSigner: false, isWritable: programmable },
+        { pubkey: mint.publicKey, isSigner: false, isWritable: true },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: maker.publicKey, isSigner: true, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: authRulesProgram, isSigner: false, isWritable: false },
+        { pubkey: ruleSetAccount, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.concat([
+        Buffer.from([43, 0]), // MintV1
+        new anchor.BN(1).toArrayLike(Buffer, "le", 8), // Amount = 1
+        Buffer.from([0]), // No authorization data
+      ]),
+    });
+
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 400_000,
+        }),
+        create,
+        mintNft
+      ),
+      [maker, mint]
+    );
+
+    return mint.publicKey;
+  }
+
+  /**
+   * Helper: Borsh-encode an optional list of unverified metadata creators
+   */
+  function creatorsData(
+    creators?: { address: PublicKey; share: number }[]
+  ): Buffer {
+    if (!creators) {
+      return Buffer.from([0]); // No creators
+    }
+    const length = Buffer.alloc(4);
+    length.writeUInt32LE(creators.length);
+    return Buffer.concat([
+      Buffer.from([1]),
+      length,
+      ...creators.map(({ address, share }) =>
+        Buffer.concat([address.toBuffer(), Buffer.from([0, share])])
+      ),
+    ]);
+  }
+
+  /**
+   * Helper: Verify a creator of an NFT (the creator signs) with VerifyCreatorV1
+   */
+  async function verifyCreator(mint: PublicKey, creator: Keypair) {
+    const instruction = new TransactionInstruction({
+      programId: TOKEN_METADATA_PROGRAM_ID,
+      keys: [
+        { pubkey: creator.publicKey, isSigner: true, isWritable: false },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: findMetadataPda(mint), isSigner: false, isWritable: true },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from([52, 0]), // Verify, CreatorV1
+    });
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(instruction),
+      [maker, creator]
+    );
+  }
+
+  /**
+   * Helper: Verify an NFT as a member of its collection (the maker is the
+   * collection's update authority) with VerifyCollectionV1
+   */
+  async function verifyCollection(mint: PublicKey, collectionMint: PublicKey) {
+    const instruction = new TransactionInstruction({
+      programId: TOKEN_METADATA_PROGRAM_ID,
+      keys: [
+        { pubkey: maker.publicKey, isSigner: true, isWritable: false },
+        { pubkey: TOKEN_METADATA_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: findMetadataPda(mint), isSigner: false, isWritable: true },
+        { pubkey: collectionMint, isSigner: false, isWritable: false },
+        {
+          pubkey: findMetadataPda(collectionMint),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: findMasterEditionPda(collectionMint),
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from([52, 1]), // Verify, CollectionV1
+    });
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(instruction),
+      [maker]
+    );
+  }
+
+  /**
+   * Helper: pNFT accounts for moving `mint` from `source` to `destination`
+   */
+  function pnftAccounts(
+    mint: PublicKey,
+    source: PublicKey,
+    destination: PublicKey,
+    ruleSet: PublicKey
+  ) {
+    return {
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      metadata: findMetadataPda(mint),
+      edition: findMasterEditionPda(mint),
+      ownerTokenRecord: findTokenRecordPda(mint, source),
+      destinationTokenRecord: findTokenRecordPda(mint, destination),
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      authorizationRulesProgram: TOKEN_AUTH_RULES_PROGRAM_ID,
+      authorizationRules: ruleSet,
+    };
+  }
+
+  /**
+   * Helper: Derive a maker's profile PDA and the PDA of their next offer
+   * (pass seed "auction", "swap_offer", "core_offer" or "collection_bid" for
+   * their next auction, swap offer, Core offer or collection bid)
+   */
+  async function nextOffer(owner: PublicKey, seed: string = "nft_offer") {
+    const [profile] = PublicKey.findProgramAddressSync(
+      [Buffer.from("maker_profile"), owner.toBuffer()],
+      program.programId
+    );
+    const profileInfo = await program.account.makerProfile.fetchNullable(
+      profile
+    );
+    const id = profileInfo ? profileInfo.offerCount : new anchor.BN(0);
+    const [offer] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(seed),
+        owner.toBuffer(),
+        id.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    return { makerProfile: profile, offerId: id, offer };
+  }
+
+  /**
+   * Helper: Unix timestamp `seconds` from now (offer expiry)
+   */
+  function expiresIn(seconds: number): anchor.BN {
+    return new anchor.BN(Math.floor(Date.now() / 1000) + seconds);
+  }
+
   /**
    * Helper: Airdrop SOL to an account
    */
@@ -118,6 +622,7 @@ describe("NFT Escrow Tests", () => {
     maker = Keypair.generate();
     taker = Keypair.generate();
     mintAuthority = Keypair.generate();
+    treasury = Keypair.generate();
 
     // Airdrop SOL to test accounts
     await airdrop(maker.publicKey);
@@ -129,6 +634,99 @@ describe("NFT Escrow Tests", () => {
     console.log("✅ Mint Authority:", mintAuthority.publicKey.toString());
   });
 
+  /**
+   * Test 0: Marketplace config (fee and treasury)
+   */
+  describe("Marketplace Config", () => {
+    it("Should let the upgrade authority initialize and update the config", async () => {
+      console.log("\n📝 Test: Initialize Marketplace Config");
+
+      [configAccount] = PublicKey.findProgramAddressSync(
+        [Buffer.from("marketplace_config")],
+        program.programId
+      );
+      const [programData] = PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+      );
+
+      // Provider wallet deployed the program, so it is the upgrade authority
+      await program.methods
+        .initializeConfig(feeBps, provider.wallet.publicKey)
+        .accounts({
+          systemProgram: SystemProgram.programId,
+          admin: provider.wallet.publicKey,
+          config: configAccount,
+          program: program.programId,
+          programData: programData,
+        })
+        .rpc();
+
+      // Point fees at the test treasury
+      await program.methods
+        .updateConfig(null, treasury.publicKey, null)
+        .accounts({
+          admin: provider.wallet.publicKey,
+          config: configAccount,
+        })
+        .rpc();
+
+      const configInfo = await program.account.marketplaceConfig.fetch(
+        configAccount
+      );
+      assert.equal(configInfo.feeBps, feeBps, "Fee should match");
+      assert.equal(
+        configInfo.treasury.toString(),
+        treasury.publicKey.toString(),
+        "Treasury should be updated"
+      );
+      console.log("  ✅ Config initialized and updated");
+
+      // Non-admins cannot update the config
+      try {
+        await program.methods
+          .updateConfig(0, null, null)
+          .accounts({
+            admin: maker.publicKey,
+            config: configAccount,
+          })
+          .signers([maker])
+          .rpc();
+
+        assert.fail("Should have thrown error for non-admin update");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "Unauthorized",
+          "Should fail with Unauthorized error"
+        );
+        console.log("  ✅ Correctly rejected non-admin update");
+      }
+
+      // Fees above the cap would leave no room for royalties
+      try {
+        await program.methods
+          .updateConfig(5_000, null, null)
+          .accounts({
+            admin: provider.wallet.publicKey,
+            config: configAccount,
+          })
+          .rpc();
+
+        assert.fail("Should have thrown error for a fee above the cap");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidFeeBps",
+          "Should fail with InvalidFeeBps error"
+        );
+        console.log("  ✅ Correctly rejected a fee above the cap");
+      }
+
+      console.log("\n✅ Marketplace config test passed!\n");
+    });
+  });
+
   /**
    * Test 1: Successfully create an NFT offer
    */
@@ -161,14 +759,12 @@ describe("NFT Escrow Tests", () => {
       );
       console.log("  ✅ Maker has NFT");
 
-      // Derive PDA accounts
-      [offerAccount] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("nft_offer"),
-          offerId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
-      );
+      // Derive PDA accounts (offer ID is allocated by the maker profile)
+      ({
+        makerProfile,
+        offerId,
+        offer: offerAccount,
+      } = await nextOffer(maker.publicKey));
 
       nftVault = await getAssociatedTokenAddress(
         nftMint,
@@ -181,17 +777,22 @@ describe("NFT Escrow Tests", () => {
 
       // Create the offer
       const tx = await program.methods
-        .makeOffer(offerId, paymentAmount)
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
         .accounts({
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           maker: maker.publicKey,
           nftMint: nftMint,
+          nftMetadata: null,
           paymentMint: paymentMint,
           makerNftAccount: makerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
           offer: offerAccount,
           nftVault: nftVault,
+          pnft: noPnft,
         })
         .signers([maker])
         .rpc();
@@ -256,11 +857,7 @@ describe("NFT Escrow Tests", () => {
     it("Should fail when token amount is zero", async () => {
       console.log("\n📝 Test: Fail on zero token amount");
 
-      const newOfferId = new anchor.BN(999);
-      const [newOfferAccount] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft_offer"), newOfferId.toArrayLike(Buffer, "le", 8)],
-        program.programId
-      );
+      const { offer: newOfferAccount } = await nextOffer(maker.publicKey);
 
       // Create another NFT for this test
       const testNftMint = await createNFT(maker.publicKey);
@@ -276,17 +873,27 @@ describe("NFT Escrow Tests", () => {
 
       try {
         await program.methods
-          .makeOffer(newOfferId, new anchor.BN(0)) // Zero amount
+          .makeOffer(
+            new anchor.BN(0),
+            expiresIn(3600),
+            null,
+            null
+          ) // Zero amount
           .accounts({
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             maker: maker.publicKey,
             nftMint: testNftMint,
+            nftMetadata: null,
             paymentMint: paymentMint,
             makerNftAccount: testMakerNftAccount,
+            config: configAccount,
+            makerProfile: makerProfile,
             offer: newOfferAccount,
             nftVault: testNftVault,
+            pnft: noPnft,
           })
           .signers([maker])
           .rpc();
@@ -303,93 +910,701 @@ describe("NFT Escrow Tests", () => {
 
       console.log("\n✅ Zero amount validation test passed!\n");
     });
-  });
 
-  /**
-   * Test 2: Successfully take an NFT offer
-   */
-  describe("Take Offer", () => {
-    it("Should successfully take an NFT offer", async () => {
-      console.log("\n📝 Test: Take NFT Offer");
+    it("Should fail when the mint can still be minted", async () => {
+      console.log("\n📝 Test: Fail on non-NFT mint");
 
-      // Create payment token accounts for maker and taker
-      makerPaymentAccount = await createAssociatedTokenAccount(
+      const { offer: newOfferAccount } = await nextOffer(maker.publicKey);
+
+      // One unit of a mint whose authority can still mint more
+      const fakeNftMint = await createMint(
         provider.connection,
-        taker, // Payer
-        paymentMint,
-        maker.publicKey
+        maker,
+        mintAuthority.publicKey,
+        null,
+        0
       );
-
-      takerPaymentAccount = await createAssociatedTokenAccount(
+      const fakeMakerNftAccount = await createAccount(
         provider.connection,
-        taker,
-        paymentMint,
-        taker.publicKey
+        maker,
+        fakeNftMint,
+        maker.publicKey
       );
-
-      // Mint payment tokens to taker
       await mintTo(
         provider.connection,
-        taker,
-        paymentMint,
-        takerPaymentAccount,
+        maker,
+        fakeNftMint,
+        fakeMakerNftAccount,
         mintAuthority,
-        200 * 1_000_000 // 200 tokens (more than needed)
-      );
-
-      const takerPaymentBefore = await getAccount(
-        provider.connection,
-        takerPaymentAccount
+        1
       );
-      console.log(
-        "  Taker payment balance before:",
-        takerPaymentBefore.amount.toString()
+      const fakeNftVault = await getAssociatedTokenAddress(
+        fakeNftMint,
+        newOfferAccount,
+        true
       );
 
-      // Derive taker's NFT account address (doesn't exist yet)
-      takerNftAccount = await getAssociatedTokenAddress(
-        nftMint,
-        taker.publicKey
-      );
+      try {
+        await program.methods
+          .makeOffer(paymentAmount, expiresIn(3600), null, null)
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            maker: maker.publicKey,
+            nftMint: fakeNftMint,
+            nftMetadata: null,
+            paymentMint: paymentMint,
+            makerNftAccount: fakeMakerNftAccount,
+            config: configAccount,
+            makerProfile: makerProfile,
+            offer: newOfferAccount,
+            nftVault: fakeNftVault,
+            pnft: noPnft,
+          })
+          .signers([maker])
+          .rpc();
 
-      // Take the offer
-      const tx = await program.methods
-        .takeOffer()
-        .accounts({
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-          taker: taker.publicKey,
-          maker: maker.publicKey,
-          nftMint: nftMint,
-          paymentMint: paymentMint,
-          takerPaymentAccount: takerPaymentAccount,
-          takerNftAccount: takerNftAccount,
-          makerPaymentAccount: makerPaymentAccount,
-          offer: offerAccount,
-          nftVault: nftVault,
-        })
-        .signers([taker])
-        .rpc();
+        assert.fail("Should have thrown error for a mintable NFT");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidNftMintAuthority",
+          "Should fail with InvalidNftMintAuthority error"
+        );
+        console.log("  ✅ Correctly rejected a mint that is not an NFT");
+      }
 
-      console.log("  Transaction:", tx);
+      console.log("\n✅ Strict NFT validation test passed!\n");
+    });
 
-      // Verify taker received NFT
-      const takerNftAccountInfo = await getAccount(
+    it("Should reject each kind of non-NFT mint with its own error", async () => {
+      console.log("\n📝 Test: Strict NFT checks");
+
+      // Mint `amounts` to the maker (first) and the taker, then revoke the
+      // mint authority so only the check under test can fail
+      const distribute = async (
+        mint: PublicKey,
+        tokenProgram: PublicKey,
+        amounts: number[]
+      ) => {
+        const holders = [maker.publicKey, taker.publicKey];
+        for (let i = 0; i < amounts.length; i++) {
+          const account = await createAccount(
+            provider.connection,
+            maker,
+            mint,
+            holders[i],
+            undefined,
+            undefined,
+            tokenProgram
+          );
+          await mintTo(
+            provider.connection,
+            maker,
+            mint,
+            account,
+            mintAuthority,
+            amounts[i],
+            [],
+            undefined,
+            tokenProgram
+          );
+        }
+        await setAuthority(
+          provider.connection,
+          maker,
+          mint,
+          mintAuthority,
+          AuthorityType.MintTokens,
+          null,
+          [],
+          undefined,
+          tokenProgram
+        );
+      };
+
+      // Token-2022 mint with 0 decimals and a single extension
+      const extensionMint = async (
+        extension: ExtensionType,
+        initExtension: (mint: PublicKey) => TransactionInstruction
+      ): Promise<PublicKey> => {
+        const mint = Keypair.generate();
+        const mintLen = getMintLen([extension]);
+        const lamports =
+          await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+        await sendAndConfirmTransaction(
+          provider.connection,
+          new Transaction().add(
+            SystemProgram.createAccount({
+              fromPubkey: maker.publicKey,
+              newAccountPubkey: mint.publicKey,
+              space: mintLen,
+              lamports,
+              programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            initExtension(mint.publicKey),
+            createInitializeMintInstruction(
+              mint.publicKey,
+              0,
+              mintAuthority.publicKey,
+              null,
+              TOKEN_2022_PROGRAM_ID
+            )
+          ),
+          [maker, mint]
+        );
+        await distribute(mint.publicKey, TOKEN_2022_PROGRAM_ID, [1]);
+        return mint.publicKey;
+      };
+
+      const expectRejected = async (
+        mint: PublicKey,
+        tokenProgram: PublicKey,
+        errorCode: string
+      ) => {
+        const { offer } = await nextOffer(maker.publicKey);
+        try {
+          await program.methods
+            .makeOffer(paymentAmount, expiresIn(3600), null, null)
+            .accounts({
+              associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              nftTokenProgram: tokenProgram,
+              paymentTokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              maker: maker.publicKey,
+              nftMint: mint,
+              nftMetadata: null,
+              paymentMint: paymentMint,
+              makerNftAccount: await getAssociatedTokenAddress(
+                mint,
+                maker.publicKey,
+                false,
+                tokenProgram
+              ),
+              config: configAccount,
+              makerProfile: makerProfile,
+              offer: offer,
+              nftVault: await getAssociatedTokenAddress(
+                mint,
+                offer,
+                true,
+                tokenProgram
+              ),
+              pnft: noPnft,
+            })
+            .signers([maker])
+            .rpc();
+
+          assert.fail(`Should have thrown error ${errorCode}`);
+        } catch (error) {
+          assert.include(
+            error.toString(),
+            errorCode,
+            `Should fail with ${errorCode} error`
+          );
+          console.log(`  ✅ Correctly rejected with ${errorCode}`);
+        }
+      };
+
+      // Fungible decimals: one raw unit of a 1-decimal mint
+      const decimalsMint = await createMint(
         provider.connection,
-        takerNftAccount
-      );
-      assert.equal(
-        takerNftAccountInfo.amount.toString(),
-        "1",
-        "Taker should have 1 NFT"
+        maker,
+        mintAuthority.publicKey,
+        null,
+        1
       );
-      console.log("  ✅ Taker received NFT");
+      await distribute(decimalsMint, TOKEN_PROGRAM_ID, [1]);
+      await expectRejected(decimalsMint, TOKEN_PROGRAM_ID, "InvalidNftDecimals");
 
-      // Verify taker paid tokens
-      const takerPaymentAfter = await getAccount(
+      // Supply of 2: the maker holds one, the taker the other
+      const supplyMint = await createMint(
         provider.connection,
-        takerPaymentAccount
+        maker,
+        mintAuthority.publicKey,
+        null,
+        0
+      );
+      await distribute(supplyMint, TOKEN_PROGRAM_ID, [1, 1]);
+      await expectRejected(supplyMint, TOKEN_PROGRAM_ID, "InvalidNftSupply");
+
+      // Token-2022 extensions that let someone else move or tax the NFT
+      const permanentDelegateMint = await extensionMint(
+        ExtensionType.PermanentDelegate,
+        (mint) =>
+          createInitializePermanentDelegateInstruction(
+            mint,
+            maker.publicKey,
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+      await expectRejected(
+        permanentDelegateMint,
+        TOKEN_2022_PROGRAM_ID,
+        "PermanentDelegateMint"
+      );
+
+      const nonTransferableMint = await extensionMint(
+        ExtensionType.NonTransferable,
+        (mint) =>
+          createInitializeNonTransferableMintInstruction(
+            mint,
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+      await expectRejected(
+        nonTransferableMint,
+        TOKEN_2022_PROGRAM_ID,
+        "NonTransferableMint"
+      );
+
+      const transferHookMint = await extensionMint(
+        ExtensionType.TransferHook,
+        (mint) =>
+          createInitializeTransferHookInstruction(
+            mint,
+            maker.publicKey,
+            Keypair.generate().publicKey, // Hook program is never invoked
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+      await expectRejected(
+        transferHookMint,
+        TOKEN_2022_PROGRAM_ID,
+        "TransferHookMint"
+      );
+
+      const transferFeeMint = await extensionMint(
+        ExtensionType.TransferFeeConfig,
+        (mint) =>
+          new TransactionInstruction({
+            programId: TOKEN_2022_PROGRAM_ID,
+            keys: [{ pubkey: mint, isSigner: false, isWritable: true }],
+            data: Buffer.concat([
+              Buffer.from([26, 0]), // TransferFeeExtension, InitializeTransferFeeConfig
+              Buffer.from([1]), // Config authority
+              maker.publicKey.toBuffer(),
+              Buffer.from([1]), // Withdraw withheld authority
+              maker.publicKey.toBuffer(),
+              new anchor.BN(100).toArrayLike(Buffer, "le", 2), // 1% fee
+              new anchor.BN(1).toArrayLike(Buffer, "le", 8), // Maximum fee
+            ]),
+          })
+      );
+      await expectRejected(
+        transferFeeMint,
+        TOKEN_2022_PROGRAM_ID,
+        "TransferFeeMint"
+      );
+
+      console.log("\n✅ Strict NFT checks test passed!\n");
+    });
+
+    it("Should reject NFTs outside the marketplace collection", async () => {
+      console.log("\n📝 Test: Marketplace collection restriction");
+
+      // Curate the storefront to a single collection
+      const storefrontCollection = Keypair.generate().publicKey;
+      await program.methods
+        .setConfigCollection(storefrontCollection)
+        .accounts({
+          admin: provider.wallet.publicKey,
+          config: configAccount,
+        })
+        .rpc();
+
+      const { offer: newOfferAccount } = await nextOffer(maker.publicKey);
+      const testNftMint = await createNFT(maker.publicKey);
+      const testMakerNftAccount = await getAssociatedTokenAddress(
+        testNftMint,
+        maker.publicKey
+      );
+      const testNftVault = await getAssociatedTokenAddress(
+        testNftMint,
+        newOfferAccount,
+        true
+      );
+
+      try {
+        await program.methods
+          .makeOffer(paymentAmount, expiresIn(3600), null, null)
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            maker: maker.publicKey,
+            nftMint: testNftMint,
+            nftMetadata: null, // NFT has no metadata, so no collection
+            paymentMint: paymentMint,
+            makerNftAccount: testMakerNftAccount,
+            config: configAccount,
+            makerProfile: makerProfile,
+            offer: newOfferAccount,
+            nftVault: testNftVault,
+            pnft: noPnft,
+          })
+          .signers([maker])
+          .rpc();
+
+        assert.fail("Should have thrown error for an uncurated NFT");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidMetadata",
+          "Should fail with InvalidMetadata error"
+        );
+        console.log("  ✅ Correctly rejected an NFT outside the collection");
+      } finally {
+        // Lift the restriction for the remaining tests
+        await program.methods
+          .setConfigCollection(null)
+          .accounts({
+            admin: provider.wallet.publicKey,
+            config: configAccount,
+          })
+          .rpc();
+      }
+
+      console.log("\n✅ Marketplace collection test passed!\n");
+    });
+
+    it("Should list a verified member of the marketplace collection", async () => {
+      console.log("\n📝 Test: Verified collection listing");
+
+      const collectionMint = await createMetadataNft(maker.publicKey);
+      const memberMint = await createMetadataNft(maker.publicKey, {
+        collection: collectionMint,
+      });
+      await verifyCollection(memberMint, collectionMint);
+
+      await program.methods
+        .setConfigCollection(collectionMint)
+        .accounts({
+          admin: provider.wallet.publicKey,
+          config: configAccount,
+        })
+        .rpc();
+
+      try {
+        const { offer: memberOffer } = await nextOffer(maker.publicKey);
+        await program.methods
+          .makeOffer(paymentAmount, expiresIn(3600), null, null)
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            maker: maker.publicKey,
+            nftMint: memberMint,
+            nftMetadata: findMetadataPda(memberMint),
+            paymentMint: paymentMint,
+            makerNftAccount: await getAssociatedTokenAddress(
+              memberMint,
+              maker.publicKey
+            ),
+            config: configAccount,
+            makerProfile: makerProfile,
+            offer: memberOffer,
+            nftVault: await getAssociatedTokenAddress(
+              memberMint,
+              memberOffer,
+              true
+            ),
+            pnft: noPnft,
+          })
+          .signers([maker])
+          .rpc();
+
+        // The offer records the collection it was listed under
+        const memberOfferInfo = await program.account.nftOffer.fetch(
+          memberOffer
+        );
+        assert.equal(
+          memberOfferInfo.collection.toString(),
+          collectionMint.toString(),
+          "Offer collection should match"
+        );
+        console.log("  ✅ Verified member listed");
+      } finally {
+        // Lift the restriction for the remaining tests
+        await program.methods
+          .setConfigCollection(null)
+          .accounts({
+            admin: provider.wallet.publicKey,
+            config: configAccount,
+          })
+          .rpc();
+      }
+
+      console.log("\n✅ Verified collection listing test passed!\n");
+    });
+
+    it("Should reject unverified and wrong-collection NFTs", async () => {
+      console.log("\n📝 Test: Collection mismatch");
+
+      const collectionMint = await createMetadataNft(maker.publicKey);
+      const otherCollectionMint = await createMetadataNft(maker.publicKey);
+      const unverifiedMint = await createMetadataNft(maker.publicKey, {
+        collection: collectionMint,
+      });
+      const otherMemberMint = await createMetadataNft(maker.publicKey, {
+        collection: otherCollectionMint,
+      });
+      await verifyCollection(otherMemberMint, otherCollectionMint);
+
+      await program.methods
+        .setConfigCollection(collectionMint)
+        .accounts({
+          admin: provider.wallet.publicKey,
+          config: configAccount,
+        })
+        .rpc();
+
+      try {
+        // Unverified member of the right collection, as a fixed-price listing
+        const { offer: unverifiedOffer } = await nextOffer(maker.publicKey);
+        try {
+          await program.methods
+            .makeOffer(paymentAmount, expiresIn(3600), null, null)
+            .accounts({
+              associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              nftTokenProgram: TOKEN_PROGRAM_ID,
+              paymentTokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              maker: maker.publicKey,
+              nftMint: unverifiedMint,
+              nftMetadata: findMetadataPda(unverifiedMint),
+              paymentMint: paymentMint,
+              makerNftAccount: await getAssociatedTokenAddress(
+                unverifiedMint,
+                maker.publicKey
+              ),
+              config: configAccount,
+              makerProfile: makerProfile,
+              offer: unverifiedOffer,
+              nftVault: await getAssociatedTokenAddress(
+                unverifiedMint,
+                unverifiedOffer,
+                true
+              ),
+              pnft: noPnft,
+            })
+            .signers([maker])
+            .rpc();
+
+          assert.fail("Should have thrown error for an unverified member");
+        } catch (error) {
+          assert.include(
+            error.toString(),
+            "CollectionMismatch",
+            "Should fail with CollectionMismatch error"
+          );
+          console.log("  ✅ Correctly rejected an unverified member");
+        }
+
+        // Verified member of another collection, as an English auction
+        const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+        try {
+          await program.methods
+            .createAuction(
+              new anchor.BN(0),
+              new anchor.BN(1_000_000),
+              expiresIn(3600),
+              new anchor.BN(0)
+            )
+            .accounts({
+              associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              nftTokenProgram: TOKEN_PROGRAM_ID,
+              paymentTokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              seller: maker.publicKey,
+              nftMint: otherMemberMint,
+              nftMetadata: findMetadataPda(otherMemberMint),
+              paymentMint: paymentMint,
+              sellerNftAccount: await getAssociatedTokenAddress(
+                otherMemberMint,
+                maker.publicKey
+              ),
+              config: configAccount,
+              makerProfile: makerProfile,
+              auction: auction,
+              nftVault: await getAssociatedTokenAddress(
+                otherMemberMint,
+                auction,
+                true
+              ),
+              paymentVault: await getAssociatedTokenAddress(
+                paymentMint,
+                auction,
+                true
+              ),
+            })
+            .signers([maker])
+            .rpc();
+
+          assert.fail("Should have thrown error for a wrong collection");
+        } catch (error) {
+          assert.include(
+            error.toString(),
+            "CollectionMismatch",
+            "Should fail with CollectionMismatch error"
+          );
+          console.log("  ✅ Correctly rejected an NFT of another collection");
+        }
+      } finally {
+        // Lift the restriction for the remaining tests
+        await program.methods
+          .setConfigCollection(null)
+          .accounts({
+            admin: provider.wallet.publicKey,
+            config: configAccount,
+          })
+          .rpc();
+      }
+
+      console.log("\n✅ Collection mismatch test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2: Successfully take an NFT offer
+   */
+  describe("Take Offer", () => {
+    it("Should successfully take an NFT offer", async () => {
+      console.log("\n📝 Test: Take NFT Offer");
+
+      // Create payment token accounts for maker and taker
+      makerPaymentAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        taker, // Payer
+        paymentMint,
+        maker.publicKey
+      );
+
+      takerPaymentAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        taker,
+        paymentMint,
+        taker.publicKey
+      );
+
+      // Mint payment tokens to taker
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        200 * 1_000_000 // 200 tokens (more than needed)
+      );
+
+      const takerPaymentBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      console.log(
+        "  Taker payment balance before:",
+        takerPaymentBefore.amount.toString()
+      );
+
+      // Derive taker's NFT account address (doesn't exist yet)
+      takerNftAccount = await getAssociatedTokenAddress(
+        nftMint,
+        taker.publicKey
+      );
+
+      // Derive treasury's payment account address (doesn't exist yet)
+      treasuryPaymentAccount = await getAssociatedTokenAddress(
+        paymentMint,
+        treasury.publicKey
+      );
+
+      // Taking at a price other than the listed one must fail
+      try {
+        await program.methods
+          .takeOffer(paymentAmount.addn(1), paymentMint, { token: {} })
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            taker: taker.publicKey,
+            maker: maker.publicKey,
+            nftMint: nftMint,
+            nftMetadata: findMetadataPda(nftMint), // No metadata: no royalties
+            paymentMint: paymentMint,
+            takerPaymentAccount: takerPaymentAccount,
+            takerNftAccount: takerNftAccount,
+            makerPaymentAccount: makerPaymentAccount,
+            config: configAccount,
+            treasury: treasury.publicKey,
+            treasuryPaymentAccount: treasuryPaymentAccount,
+            offer: offerAccount,
+            nftVault: nftVault,
+            pnft: noPnft,
+          })
+          .signers([taker])
+          .rpc();
+
+        assert.fail("Should have thrown error for price mismatch");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "PriceMismatch",
+          "Should fail with PriceMismatch error"
+        );
+        console.log("  ✅ Correctly rejected mismatched price");
+      }
+
+      // Take the offer
+      const tx = await program.methods
+        .takeOffer(paymentAmount, paymentMint, { token: {} })
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: nftMint,
+          nftMetadata: findMetadataPda(nftMint), // No metadata: no royalties
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          takerNftAccount: takerNftAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: offerAccount,
+          nftVault: nftVault,
+          pnft: noPnft,
+        })
+        .signers([taker])
+        .rpc();
+
+      console.log("  Transaction:", tx);
+
+      // Verify taker received NFT
+      const takerNftAccountInfo = await getAccount(
+        provider.connection,
+        takerNftAccount
+      );
+      assert.equal(
+        takerNftAccountInfo.amount.toString(),
+        "1",
+        "Taker should have 1 NFT"
+      );
+      console.log("  ✅ Taker received NFT");
+
+      // Verify taker paid tokens
+      const takerPaymentAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
       );
       const expectedBalance = 200 * 1_000_000 - Number(paymentAmount);
       assert.equal(
@@ -399,115 +1614,3020 @@ describe("NFT Escrow Tests", () => {
       );
       console.log("  ✅ Taker paid correct amount");
 
-      // Verify maker received payment
-      const makerPaymentAccountInfo = await getAccount(
-        provider.connection,
-        makerPaymentAccount
+      // Verify maker received payment
+      const makerPaymentAccountInfo = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      assert.equal(
+        makerPaymentAccountInfo.amount.toString(),
+        (Number(paymentAmount) - feeAmount).toString(),
+        "Maker should receive payment minus fee"
+      );
+      console.log("  ✅ Maker received payment");
+
+      // Verify treasury received the marketplace fee
+      const treasuryPaymentInfo = await getAccount(
+        provider.connection,
+        treasuryPaymentAccount
+      );
+      assert.equal(
+        treasuryPaymentInfo.amount.toString(),
+        feeAmount.toString(),
+        "Treasury should receive fee"
+      );
+      console.log("  ✅ Treasury received fee");
+
+      // Verify vault is closed
+      try {
+        await getAccount(provider.connection, nftVault);
+        assert.fail("Vault should be closed");
+      } catch (error) {
+        console.log("  ✅ Vault closed successfully");
+      }
+
+      // Verify offer account is closed
+      try {
+        await program.account.nftOffer.fetch(offerAccount);
+        assert.fail("Offer account should be closed");
+      } catch (error) {
+        console.log("  ✅ Offer account closed successfully");
+      }
+
+      console.log("\n✅ Take offer test passed!\n");
+    });
+
+    it("Should take a native SOL offer with lamport payments", async () => {
+      console.log("\n📝 Test: Native SOL Offer");
+
+      const solPrice = new anchor.BN(LAMPORTS_PER_SOL / 10); // 0.1 SOL
+      const solFee = (Number(solPrice) * feeBps) / 10_000;
+      const solNftMint = await createNFT(maker.publicKey);
+      const solMakerNftAccount = await getAssociatedTokenAddress(
+        solNftMint,
+        maker.publicKey
+      );
+      const { offer: solOfferAccount } = await nextOffer(maker.publicKey);
+      const solNftVault = await getAssociatedTokenAddress(
+        solNftMint,
+        solOfferAccount,
+        true
+      );
+
+      // Create an offer priced in lamports (no payment mint)
+      await program.methods
+        .makeOffer(solPrice, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: solNftMint,
+          nftMetadata: null,
+          paymentMint: null,
+          makerNftAccount: solMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: solOfferAccount,
+          nftVault: solNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      const offerInfo = await program.account.nftOffer.fetch(solOfferAccount);
+      assert.deepEqual(
+        offerInfo.paymentMode,
+        { nativeSol: {} },
+        "Offer should be priced in native SOL"
+      );
+
+      // Fund the treasury so it stays rent-exempt after receiving the fee
+      await airdrop(treasury.publicKey);
+      const makerLamportsBefore = await provider.connection.getBalance(
+        maker.publicKey
+      );
+      const treasuryLamportsBefore = await provider.connection.getBalance(
+        treasury.publicKey
+      );
+
+      const solTakeAccounts = {
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        taker: taker.publicKey,
+        maker: maker.publicKey,
+        nftMint: solNftMint,
+        nftMetadata: findMetadataPda(solNftMint),
+        paymentMint: null,
+        takerPaymentAccount: null,
+        takerNftAccount: await getAssociatedTokenAddress(
+          solNftMint,
+          taker.publicKey
+        ),
+        makerPaymentAccount: null,
+        config: configAccount,
+        treasury: treasury.publicKey,
+        treasuryPaymentAccount: null,
+        offer: solOfferAccount,
+        nftVault: solNftVault,
+        pnft: noPnft,
+      };
+
+      // A taker expecting a wrapped SOL offer must not pay in lamports
+      try {
+        await program.methods
+          .takeOffer(solPrice, NATIVE_MINT, { token: {} })
+          .accounts(solTakeAccounts)
+          .signers([taker])
+          .rpc();
+
+        assert.fail("Should have thrown error for payment mode mismatch");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "PaymentModeMismatch",
+          "Should fail with PaymentModeMismatch error"
+        );
+        console.log("  ✅ Correctly rejected a wrapped SOL expectation");
+      }
+
+      // Take the offer paying lamports (no payment token accounts)
+      await program.methods
+        .takeOffer(solPrice, NATIVE_MINT, { nativeSol: {} })
+        .accounts(solTakeAccounts)
+        .signers([taker])
+        .rpc();
+
+      // Maker receives price minus fee, plus rent back from the closed offer
+      const makerLamportsAfter = await provider.connection.getBalance(
+        maker.publicKey
+      );
+      assert.isAtLeast(
+        makerLamportsAfter - makerLamportsBefore,
+        Number(solPrice) - solFee,
+        "Maker should receive SOL payment minus fee"
+      );
+      console.log("  ✅ Maker received SOL payment");
+
+      const treasuryLamportsAfter = await provider.connection.getBalance(
+        treasury.publicKey
+      );
+      assert.equal(
+        treasuryLamportsAfter - treasuryLamportsBefore,
+        solFee,
+        "Treasury should receive SOL fee"
+      );
+      console.log("  ✅ Treasury received SOL fee");
+
+      console.log("\n✅ Native SOL offer test passed!\n");
+    });
+
+    it("Should reject takers other than a private offer's counterparty", async () => {
+      console.log("\n📝 Test: Private Offer");
+
+      const otcCounterparty = Keypair.generate();
+      const privateNftMint = await createNFT(maker.publicKey);
+      const privateMakerNftAccount = await getAssociatedTokenAddress(
+        privateNftMint,
+        maker.publicKey
+      );
+      const { offer: privateOfferAccount } = await nextOffer(maker.publicKey);
+      const privateNftVault = await getAssociatedTokenAddress(
+        privateNftMint,
+        privateOfferAccount,
+        true
+      );
+
+      // Create an offer only the OTC counterparty can fill
+      await program.methods
+        .makeOffer(
+          paymentAmount,
+          expiresIn(3600),
+          otcCounterparty.publicKey,
+          null
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: privateNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: privateMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: privateOfferAccount,
+          nftVault: privateNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      const offerInfo = await program.account.nftOffer.fetch(
+        privateOfferAccount
+      );
+      assert.equal(
+        offerInfo.allowedTaker.toString(),
+        otcCounterparty.publicKey.toString(),
+        "Allowed taker should match"
+      );
+
+      // Anyone else is rejected
+      try {
+        await program.methods
+          .takeOffer(paymentAmount, paymentMint, { token: {} })
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            taker: taker.publicKey,
+            maker: maker.publicKey,
+            nftMint: privateNftMint,
+            nftMetadata: findMetadataPda(privateNftMint),
+            paymentMint: paymentMint,
+            takerPaymentAccount: takerPaymentAccount,
+            takerNftAccount: await getAssociatedTokenAddress(
+              privateNftMint,
+              taker.publicKey
+            ),
+            makerPaymentAccount: makerPaymentAccount,
+            config: configAccount,
+            treasury: treasury.publicKey,
+            treasuryPaymentAccount: treasuryPaymentAccount,
+            offer: privateOfferAccount,
+            nftVault: privateNftVault,
+            pnft: noPnft,
+          })
+          .signers([taker])
+          .rpc();
+
+        assert.fail("Should have thrown error for disallowed taker");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "TakerNotAllowed",
+          "Should fail with TakerNotAllowed error"
+        );
+        console.log("  ✅ Correctly rejected other taker");
+      }
+
+      console.log("\n✅ Private offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2b: Every NFT / payment token program combination
+   */
+  describe("Mixed Token Programs", () => {
+    const programs = [
+      { name: "SPL Token", id: TOKEN_PROGRAM_ID },
+      { name: "Token-2022", id: TOKEN_2022_PROGRAM_ID },
+    ];
+
+    for (const nftProgram of programs) {
+      for (const paymentProgram of programs) {
+        it(`Should trade a ${nftProgram.name} NFT for ${paymentProgram.name} payment`, async () => {
+          console.log(
+            `\n📝 Test: ${nftProgram.name} NFT / ${paymentProgram.name} payment`
+          );
+
+          const mixedNftMint = await createNFT(maker.publicKey, nftProgram.id);
+          const mixedPaymentMint = await createToken(paymentProgram.id);
+          const mixedMakerNftAccount = await getAssociatedTokenAddress(
+            mixedNftMint,
+            maker.publicKey,
+            false,
+            nftProgram.id
+          );
+          const { offer: mixedOfferAccount } = await nextOffer(
+            maker.publicKey
+          );
+          const mixedNftVault = await getAssociatedTokenAddress(
+            mixedNftMint,
+            mixedOfferAccount,
+            true,
+            nftProgram.id
+          );
+
+          await program.methods
+            .makeOffer(paymentAmount, expiresIn(3600), null, null)
+            .accounts({
+              associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              nftTokenProgram: nftProgram.id,
+              paymentTokenProgram: paymentProgram.id,
+              systemProgram: SystemProgram.programId,
+              maker: maker.publicKey,
+              nftMint: mixedNftMint,
+              nftMetadata: null,
+              paymentMint: mixedPaymentMint,
+              makerNftAccount: mixedMakerNftAccount,
+              config: configAccount,
+              makerProfile: makerProfile,
+              offer: mixedOfferAccount,
+              nftVault: mixedNftVault,
+              pnft: noPnft,
+            })
+            .signers([maker])
+            .rpc();
+
+          // Fund the taker in the payment token
+          const mixedTakerPaymentAccount = await createAssociatedTokenAccount(
+            provider.connection,
+            taker,
+            mixedPaymentMint,
+            taker.publicKey,
+            undefined,
+            paymentProgram.id
+          );
+          await mintTo(
+            provider.connection,
+            taker,
+            mixedPaymentMint,
+            mixedTakerPaymentAccount,
+            mintAuthority,
+            Number(paymentAmount),
+            [],
+            undefined,
+            paymentProgram.id
+          );
+
+          const mixedTakerNftAccount = await getAssociatedTokenAddress(
+            mixedNftMint,
+            taker.publicKey,
+            false,
+            nftProgram.id
+          );
+          const mixedMakerPaymentAccount = await getAssociatedTokenAddress(
+            mixedPaymentMint,
+            maker.publicKey,
+            false,
+            paymentProgram.id
+          );
+
+          await program.methods
+            .takeOffer(paymentAmount, mixedPaymentMint, { token: {} })
+            .accounts({
+              associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+              nftTokenProgram: nftProgram.id,
+              paymentTokenProgram: paymentProgram.id,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              taker: taker.publicKey,
+              maker: maker.publicKey,
+              nftMint: mixedNftMint,
+              nftMetadata: findMetadataPda(mixedNftMint),
+              paymentMint: mixedPaymentMint,
+              takerPaymentAccount: mixedTakerPaymentAccount,
+              takerNftAccount: mixedTakerNftAccount,
+              makerPaymentAccount: mixedMakerPaymentAccount,
+              config: configAccount,
+              treasury: treasury.publicKey,
+              treasuryPaymentAccount: await getAssociatedTokenAddress(
+                mixedPaymentMint,
+                treasury.publicKey,
+                false,
+                paymentProgram.id
+              ),
+              offer: mixedOfferAccount,
+              nftVault: mixedNftVault,
+              pnft: noPnft,
+            })
+            .signers([taker])
+            .rpc();
+
+          // Verify NFT and payment moved under their own token programs
+          const takerNftInfo = await getAccount(
+            provider.connection,
+            mixedTakerNftAccount,
+            undefined,
+            nftProgram.id
+          );
+          assert.equal(takerNftInfo.amount.toString(), "1");
+          const makerPaymentInfo = await getAccount(
+            provider.connection,
+            mixedMakerPaymentAccount,
+            undefined,
+            paymentProgram.id
+          );
+          assert.equal(
+            makerPaymentInfo.amount.toString(),
+            (Number(paymentAmount) - feeAmount).toString()
+          );
+          console.log("  ✅ NFT and payment settled");
+        });
+      }
+    }
+  });
+
+  /**
+   * Test 2c: Dutch auction listing
+   */
+  describe("Dutch Auction", () => {
+    it("Should charge the decayed price of a Dutch auction", async () => {
+      console.log("\n📝 Test: Dutch Auction Offer");
+
+      const startPrice = new anchor.BN(100 * 1_000_000);
+      const endPrice = new anchor.BN(10 * 1_000_000);
+      const dutchNftMint = await createNFT(maker.publicKey);
+      const dutchMakerNftAccount = await getAssociatedTokenAddress(
+        dutchNftMint,
+        maker.publicKey
+      );
+      const { offer: dutchOfferAccount } = await nextOffer(maker.publicKey);
+      const dutchNftVault = await getAssociatedTokenAddress(
+        dutchNftMint,
+        dutchOfferAccount,
+        true
+      );
+
+      // Price started falling 30 minutes into a one hour auction
+      await program.methods
+        .makeDutchOffer(
+          {
+            startPrice,
+            endPrice,
+            startTime: expiresIn(-1800),
+            duration: new anchor.BN(3600),
+          },
+          expiresIn(3600),
+          null,
+          null
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: dutchNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: dutchMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: dutchOfferAccount,
+          nftVault: dutchNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Top up taker with enough for the start price
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(startPrice)
+      );
+      const takerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+
+      // Taker is willing to pay up to the start price
+      await program.methods
+        .takeOffer(startPrice, paymentMint, { token: {} })
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: dutchNftMint,
+          nftMetadata: findMetadataPda(dutchNftMint),
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          takerNftAccount: await getAssociatedTokenAddress(
+            dutchNftMint,
+            taker.publicKey
+          ),
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: dutchOfferAccount,
+          nftVault: dutchNftVault,
+          pnft: noPnft,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Roughly halfway between start and end price
+      const takerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      const paid =
+        Number(takerBalanceBefore.amount) - Number(takerBalanceAfter.amount);
+      assert.isBelow(paid, Number(startPrice), "Should pay below start price");
+      assert.isAbove(paid, Number(endPrice), "Should pay above end price");
+      console.log("  ✅ Taker paid decayed price:", paid);
+
+      console.log("\n✅ Dutch auction test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2d: English auction with escrowed bids
+   */
+  describe("English Auction", () => {
+    it("Should refund outbid bidders and settle to the winner", async () => {
+      console.log("\n📝 Test: English Auction");
+
+      const bidder = Keypair.generate();
+      await airdrop(bidder.publicKey);
+      const bidderPaymentAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        bidder,
+        paymentMint,
+        bidder.publicKey
+      );
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      await mintTo(
+        provider.connection,
+        bidder,
+        paymentMint,
+        bidderPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+
+      const auctionNftMint = await createNFT(maker.publicKey);
+      const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+      const auctionNftVault = await getAssociatedTokenAddress(
+        auctionNftMint,
+        auction,
+        true
+      );
+      const paymentVault = await getAssociatedTokenAddress(
+        paymentMint,
+        auction,
+        true
+      );
+
+      // Reserve of 50 tokens, bids must rise by at least 5 tokens
+      await program.methods
+        .createAuction(
+          new anchor.BN(50 * 1_000_000),
+          new anchor.BN(5 * 1_000_000),
+          expiresIn(5),
+          new anchor.BN(0)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          seller: maker.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          sellerNftAccount: await getAssociatedTokenAddress(
+            auctionNftMint,
+            maker.publicKey
+          ),
+          config: configAccount,
+          makerProfile: makerProfile,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([maker])
+        .rpc();
+      console.log("  ✅ Auction created");
+
+      const firstBid = new anchor.BN(60 * 1_000_000);
+      const secondBid = new anchor.BN(70 * 1_000_000);
+      const takerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+
+      // Taker opens the bidding
+      await program.methods
+        .placeBid(firstBid)
+        .accounts({
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          bidder: taker.publicKey,
+          paymentMint: paymentMint,
+          bidderPaymentAccount: takerPaymentAccount,
+          previousBidder: null,
+          previousBidderPaymentAccount: null,
+          auction: auction,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Second bidder outbids the taker
+      await program.methods
+        .placeBid(secondBid)
+        .accounts({
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          bidder: bidder.publicKey,
+          paymentMint: paymentMint,
+          bidderPaymentAccount: bidderPaymentAccount,
+          previousBidder: taker.publicKey,
+          previousBidderPaymentAccount: takerPaymentAccount,
+          auction: auction,
+          paymentVault: paymentVault,
+        })
+        .signers([bidder])
+        .rpc();
+
+      // Verify taker was refunded and only the highest bid is escrowed
+      const takerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      assert.equal(
+        takerBalanceAfter.amount.toString(),
+        takerBalanceBefore.amount.toString(),
+        "Outbid taker should be refunded"
+      );
+      const vaultInfo = await getAccount(provider.connection, paymentVault);
+      assert.equal(vaultInfo.amount.toString(), secondBid.toString());
+      console.log("  ✅ Outbid bidder refunded");
+
+      // Wait for the auction to end
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+
+      const winnerNftAccount = await getAssociatedTokenAddress(
+        auctionNftMint,
+        bidder.publicKey
+      );
+      const sellerBalanceBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      await program.methods
+        .settleAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          caller: taker.publicKey,
+          seller: maker.publicKey,
+          highestBidder: bidder.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: findMetadataPda(auctionNftMint),
+          paymentMint: paymentMint,
+          winnerNftAccount: winnerNftAccount,
+          bidderPaymentAccount: null,
+          sellerNftAccount: null,
+          sellerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify winner received the NFT and the auction was closed
+      const winnerNftInfo = await getAccount(
+        provider.connection,
+        winnerNftAccount
+      );
+      assert.equal(winnerNftInfo.amount.toString(), "1");
+      const auctionInfo = await provider.connection.getAccountInfo(auction);
+      assert.isNull(auctionInfo, "Auction account should be closed");
+      console.log("  ✅ Winner received NFT");
+
+      // Seller receives the winning bid minus the marketplace fee (no royalties)
+      const sellerBalanceAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      const secondBidFee = (Number(secondBid) * feeBps) / 10_000;
+      assert.equal(
+        Number(sellerBalanceAfter.amount) - Number(sellerBalanceBefore.amount),
+        Number(secondBid) - secondBidFee,
+        "Seller should receive the bid minus the fee"
+      );
+      console.log("  ✅ Seller paid the winning bid minus the fee");
+
+      console.log("\n✅ English auction test passed!\n");
+    });
+
+    it("Should let the seller cancel an auction with no bids", async () => {
+      console.log("\n📝 Test: Cancel Auction");
+
+      const auctionNftMint = await createNFT(maker.publicKey);
+      const sellerNftAccount = await getAssociatedTokenAddress(
+        auctionNftMint,
+        maker.publicKey
+      );
+      const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+      const auctionNftVault = await getAssociatedTokenAddress(
+        auctionNftMint,
+        auction,
+        true
+      );
+      const paymentVault = await getAssociatedTokenAddress(
+        paymentMint,
+        auction,
+        true
+      );
+
+      await program.methods
+        .createAuction(
+          new anchor.BN(0),
+          new anchor.BN(1_000_000),
+          expiresIn(3600),
+          new anchor.BN(300)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          seller: maker.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          sellerNftAccount: sellerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([maker])
+        .rpc();
+
+      await program.methods
+        .cancelAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          seller: maker.publicKey,
+          nftMint: auctionNftMint,
+          paymentMint: paymentMint,
+          sellerNftAccount: sellerNftAccount,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Verify NFT returned to seller
+      const sellerNftInfo = await getAccount(
+        provider.connection,
+        sellerNftAccount
+      );
+      assert.equal(sellerNftInfo.amount.toString(), "1");
+      console.log("  ✅ NFT returned to seller");
+
+      console.log("\n✅ Cancel auction test passed!\n");
+    });
+
+    it("Should refund the highest bid when the NFT vault is frozen", async () => {
+      console.log("\n📝 Test: Settle Auction With Frozen Vault");
+
+      // NFT whose freeze authority is kept by the mint authority
+      const auctionNftMint = await createNFT(
+        maker.publicKey,
+        TOKEN_PROGRAM_ID,
+        mintAuthority.publicKey
+      );
+      const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+      const auctionNftVault = await getAssociatedTokenAddress(
+        auctionNftMint,
+        auction,
+        true
+      );
+      const paymentVault = await getAssociatedTokenAddress(
+        paymentMint,
+        auction,
+        true
+      );
+
+      await program.methods
+        .createAuction(
+          new anchor.BN(0),
+          new anchor.BN(1_000_000),
+          expiresIn(5),
+          new anchor.BN(0)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          seller: maker.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          sellerNftAccount: await getAssociatedTokenAddress(
+            auctionNftMint,
+            maker.publicKey
+          ),
+          config: configAccount,
+          makerProfile: makerProfile,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([maker])
+        .rpc();
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      const takerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      await program.methods
+        .placeBid(new anchor.BN(10 * 1_000_000))
+        .accounts({
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          bidder: taker.publicKey,
+          paymentMint: paymentMint,
+          bidderPaymentAccount: takerPaymentAccount,
+          previousBidder: null,
+          previousBidderPaymentAccount: null,
+          auction: auction,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Freeze authority locks the escrowed NFT in place
+      await freezeAccount(
+        provider.connection,
+        maker,
+        auctionNftVault,
+        auctionNftMint,
+        mintAuthority
+      );
+
+      // Wait for the auction to end
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+
+      await program.methods
+        .settleAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          caller: taker.publicKey,
+          seller: maker.publicKey,
+          highestBidder: taker.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: findMetadataPda(auctionNftMint),
+          paymentMint: paymentMint,
+          winnerNftAccount: null,
+          bidderPaymentAccount: takerPaymentAccount,
+          sellerNftAccount: null,
+          sellerPaymentAccount: null,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: null,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify the bid was refunded in full and the auction was closed
+      const takerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      assert.equal(
+        takerBalanceAfter.amount.toString(),
+        takerBalanceBefore.amount.toString(),
+        "Highest bidder should be refunded"
+      );
+      const auctionInfo = await provider.connection.getAccountInfo(auction);
+      assert.isNull(auctionInfo, "Auction account should be closed");
+      console.log("  ✅ Highest bid refunded");
+
+      console.log("\n✅ Frozen vault settlement test passed!\n");
+    });
+
+    it("Should settle and cancel auctions whose payment vault holds dust", async () => {
+      console.log("\n📝 Test: Auction Payment Vault Dust");
+
+      // One auction to settle with a bid, one to cancel without bids
+      const auctions = [];
+      for (const endsIn of [5, 3600]) {
+        const auctionNftMint = await createNFT(maker.publicKey);
+        const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+        const auctionNftVault = await getAssociatedTokenAddress(
+          auctionNftMint,
+          auction,
+          true
+        );
+        const paymentVault = await getAssociatedTokenAddress(
+          paymentMint,
+          auction,
+          true
+        );
+        await program.methods
+          .createAuction(
+            new anchor.BN(0),
+            new anchor.BN(1_000_000),
+            expiresIn(endsIn),
+            new anchor.BN(0)
+          )
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            seller: maker.publicKey,
+            nftMint: auctionNftMint,
+            nftMetadata: null,
+            paymentMint: paymentMint,
+            sellerNftAccount: await getAssociatedTokenAddress(
+              auctionNftMint,
+              maker.publicKey
+            ),
+            config: configAccount,
+            makerProfile: makerProfile,
+            auction: auction,
+            nftVault: auctionNftVault,
+            paymentVault: paymentVault,
+          })
+          .signers([maker])
+          .rpc();
+        auctions.push({ auctionNftMint, auction, auctionNftVault, paymentVault });
+      }
+      const [settled, cancelled] = auctions;
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      const bid = new anchor.BN(10 * 1_000_000);
+      await program.methods
+        .placeBid(bid)
+        .accounts({
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          bidder: taker.publicKey,
+          paymentMint: paymentMint,
+          bidderPaymentAccount: takerPaymentAccount,
+          previousBidder: null,
+          previousBidderPaymentAccount: null,
+          auction: settled.auction,
+          paymentVault: settled.paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Anyone can send a stray token to the public payment vaults
+      for (const { paymentVault } of auctions) {
+        await mintTo(
+          provider.connection,
+          taker,
+          paymentMint,
+          paymentVault,
+          mintAuthority,
+          1
+        );
+      }
+
+      // Cancelling still returns the NFT
+      const sellerNftAccount = await getAssociatedTokenAddress(
+        cancelled.auctionNftMint,
+        maker.publicKey
+      );
+      await program.methods
+        .cancelAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          seller: maker.publicKey,
+          nftMint: cancelled.auctionNftMint,
+          paymentMint: paymentMint,
+          sellerNftAccount: sellerNftAccount,
+          auction: cancelled.auction,
+          nftVault: cancelled.auctionNftVault,
+          paymentVault: cancelled.paymentVault,
+        })
+        .signers([maker])
+        .rpc();
+      const sellerNftInfo = await getAccount(
+        provider.connection,
+        sellerNftAccount
+      );
+      assert.equal(sellerNftInfo.amount.toString(), "1");
+      console.log("  ✅ Auction cancelled despite dust in the payment vault");
+
+      // Wait for the other auction to end, then settling still pays out
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+
+      const winnerNftAccount = await getAssociatedTokenAddress(
+        settled.auctionNftMint,
+        taker.publicKey
+      );
+      await program.methods
+        .settleAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          caller: taker.publicKey,
+          seller: maker.publicKey,
+          highestBidder: taker.publicKey,
+          nftMint: settled.auctionNftMint,
+          nftMetadata: findMetadataPda(settled.auctionNftMint),
+          paymentMint: paymentMint,
+          winnerNftAccount: winnerNftAccount,
+          bidderPaymentAccount: null,
+          sellerNftAccount: null,
+          sellerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          auction: settled.auction,
+          nftVault: settled.auctionNftVault,
+          paymentVault: settled.paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+      const winnerNftInfo = await getAccount(
+        provider.connection,
+        winnerNftAccount
+      );
+      assert.equal(winnerNftInfo.amount.toString(), "1");
+      console.log("  ✅ Auction settled despite dust in the payment vault");
+
+      // Both payment vaults were left open holding only the dust
+      for (const { auction, paymentVault } of auctions) {
+        const vaultInfo = await getAccount(provider.connection, paymentVault);
+        assert.equal(vaultInfo.amount.toString(), "1");
+        const auctionInfo = await provider.connection.getAccountInfo(auction);
+        assert.isNull(auctionInfo, "Auction account should be closed");
+      }
+      console.log("  ✅ Dusted payment vaults left open");
+
+      console.log("\n✅ Auction vault dust test passed!\n");
+    });
+
+    it("Should extend a sniped auction and refund a bid below the reserve", async () => {
+      console.log("\n📝 Test: Auction Extension and Reserve");
+
+      const auctionNftMint = await createNFT(maker.publicKey);
+      const sellerNftAccount = await getAssociatedTokenAddress(
+        auctionNftMint,
+        maker.publicKey
+      );
+      const { offer: auction } = await nextOffer(maker.publicKey, "auction");
+      const auctionNftVault = await getAssociatedTokenAddress(
+        auctionNftMint,
+        auction,
+        true
+      );
+      const paymentVault = await getAssociatedTokenAddress(
+        paymentMint,
+        auction,
+        true
+      );
+      const auctionAccounts = {
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        seller: maker.publicKey,
+        nftMint: auctionNftMint,
+        nftMetadata: null,
+        paymentMint: paymentMint,
+        sellerNftAccount: sellerNftAccount,
+        config: configAccount,
+        makerProfile: makerProfile,
+        auction: auction,
+        nftVault: auctionNftVault,
+        paymentVault: paymentVault,
+      };
+
+      // Extension windows and durations are capped
+      for (const [endsIn, window] of [
+        [60, 60 * 60 + 1],
+        [31 * 24 * 60 * 60, 0],
+      ]) {
+        try {
+          await program.methods
+            .createAuction(
+              new anchor.BN(0),
+              new anchor.BN(1_000_000),
+              expiresIn(endsIn),
+              new anchor.BN(window)
+            )
+            .accounts(auctionAccounts)
+            .signers([maker])
+            .rpc();
+
+          assert.fail("Should have thrown error for an unbounded auction");
+        } catch (error) {
+          assert.include(
+            error.toString(),
+            "InvalidAuction",
+            "Should fail with InvalidAuction error"
+          );
+        }
+      }
+      console.log("  ✅ Rejected an oversized window and duration");
+
+      // Ends in 6 seconds; bids in the last 12 seconds push the end back
+      const reservePrice = new anchor.BN(50 * 1_000_000);
+      await program.methods
+        .createAuction(
+          reservePrice,
+          new anchor.BN(1_000_000),
+          expiresIn(6),
+          new anchor.BN(12)
+        )
+        .accounts(auctionAccounts)
+        .signers([maker])
+        .rpc();
+      const { endTime: originalEnd } = await program.account.auction.fetch(
+        auction
+      );
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      const takerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      await program.methods
+        .placeBid(new anchor.BN(10 * 1_000_000))
+        .accounts({
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          bidder: taker.publicKey,
+          paymentMint: paymentMint,
+          bidderPaymentAccount: takerPaymentAccount,
+          previousBidder: null,
+          previousBidderPaymentAccount: null,
+          auction: auction,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      const { endTime: extendedEnd } = await program.account.auction.fetch(
+        auction
+      );
+      assert.isTrue(
+        extendedEnd.gt(originalEnd),
+        "Bid inside the window should extend the auction"
+      );
+      console.log("  ✅ Sniping bid extended the end time");
+
+      // Still running after the original end time
+      await new Promise((resolve) => setTimeout(resolve, 7000));
+      try {
+        await program.methods
+          .settleAuction()
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            caller: taker.publicKey,
+            seller: maker.publicKey,
+            highestBidder: taker.publicKey,
+            nftMint: auctionNftMint,
+            nftMetadata: findMetadataPda(auctionNftMint),
+            paymentMint: paymentMint,
+            winnerNftAccount: null,
+            bidderPaymentAccount: takerPaymentAccount,
+            sellerNftAccount: sellerNftAccount,
+            sellerPaymentAccount: null,
+            config: configAccount,
+            treasury: treasury.publicKey,
+            treasuryPaymentAccount: null,
+            auction: auction,
+            nftVault: auctionNftVault,
+            paymentVault: paymentVault,
+          })
+          .signers([taker])
+          .rpc();
+
+        assert.fail("Should have thrown error for an extended auction");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "AuctionNotEnded",
+          "Should fail with AuctionNotEnded error"
+        );
+        console.log("  ✅ Extended auction could not be settled early");
+      }
+
+      // After the extension the bid is below the reserve
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+      await program.methods
+        .settleAuction()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          caller: taker.publicKey,
+          seller: maker.publicKey,
+          highestBidder: taker.publicKey,
+          nftMint: auctionNftMint,
+          nftMetadata: findMetadataPda(auctionNftMint),
+          paymentMint: paymentMint,
+          winnerNftAccount: null,
+          bidderPaymentAccount: takerPaymentAccount,
+          sellerNftAccount: sellerNftAccount,
+          sellerPaymentAccount: null,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: null,
+          auction: auction,
+          nftVault: auctionNftVault,
+          paymentVault: paymentVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // NFT returned to the seller and the bid refunded in full
+      const sellerNftInfo = await getAccount(
+        provider.connection,
+        sellerNftAccount
+      );
+      assert.equal(sellerNftInfo.amount.toString(), "1");
+      const takerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      assert.equal(
+        takerBalanceAfter.amount.toString(),
+        takerBalanceBefore.amount.toString(),
+        "Bid below the reserve should be refunded"
+      );
+      console.log("  ✅ Reserve not met: NFT returned and bid refunded");
+
+      console.log("\n✅ Auction extension and reserve test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2e: NFT-for-NFT swap offer
+   */
+  describe("Swap Offer", () => {
+    it("Should swap NFTs with a taker-paid top-up", async () => {
+      console.log("\n📝 Test: Swap Offer");
+
+      const topUp = new anchor.BN(10 * 1_000_000);
+      const offeredMint = await createNFT(maker.publicKey);
+      const requestedMint = await createNFT(taker.publicKey);
+      const { offer: swapOffer } = await nextOffer(maker.publicKey, "swap_offer");
+      const swapNftVault = await getAssociatedTokenAddress(
+        offeredMint,
+        swapOffer,
+        true
+      );
+
+      // Maker asks for the taker's NFT plus 10 tokens
+      await program.methods
+        .makeSwapOffer(
+          { mint: [requestedMint] },
+          { amount: topUp, direction: { takerPays: {} } },
+          expiresIn(3600)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: offeredMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: await getAssociatedTokenAddress(
+            offeredMint,
+            maker.publicKey
+          ),
+          makerPaymentAccount: null,
+          config: configAccount,
+          makerProfile: makerProfile,
+          swapOffer: swapOffer,
+          nftVault: swapNftVault,
+          topUpVault: null,
+        })
+        .signers([maker])
+        .rpc();
+      console.log("  ✅ Swap offer created");
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(topUp)
+      );
+      const makerBalanceBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+
+      const makerRequestedAccount = await getAssociatedTokenAddress(
+        requestedMint,
+        maker.publicKey
+      );
+      const takerOfferedAccount = await getAssociatedTokenAddress(
+        offeredMint,
+        taker.publicKey
+      );
+      await program.methods
+        .takeSwapOffer()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          requestedTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: offeredMint,
+          nftMetadata: findMetadataPda(offeredMint),
+          requestedMint: requestedMint,
+          requestedMetadata: findMetadataPda(requestedMint),
+          paymentMint: paymentMint,
+          takerRequestedAccount: await getAssociatedTokenAddress(
+            requestedMint,
+            taker.publicKey
+          ),
+          makerRequestedAccount: makerRequestedAccount,
+          takerNftAccount: takerOfferedAccount,
+          takerPaymentAccount: takerPaymentAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          swapOffer: swapOffer,
+          nftVault: swapNftVault,
+          topUpVault: null,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify both NFTs changed hands and the maker got the top-up minus the fee
+      const makerRequestedInfo = await getAccount(
+        provider.connection,
+        makerRequestedAccount
+      );
+      assert.equal(makerRequestedInfo.amount.toString(), "1");
+      const takerOfferedInfo = await getAccount(
+        provider.connection,
+        takerOfferedAccount
+      );
+      assert.equal(takerOfferedInfo.amount.toString(), "1");
+      const makerBalanceAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      const topUpFee = (Number(topUp) * feeBps) / 10_000;
+      assert.equal(
+        Number(makerBalanceAfter.amount - makerBalanceBefore.amount),
+        Number(topUp) - topUpFee,
+        "Maker should receive the top-up minus the fee"
+      );
+      console.log("  ✅ NFTs swapped with top-up");
+
+      console.log("\n✅ Swap offer test passed!\n");
+    });
+
+    it("Should swap for a collection member with a maker-paid top-up", async () => {
+      console.log("\n📝 Test: Collection Swap With Maker Top-Up");
+
+      const topUp = new anchor.BN(5 * 1_000_000);
+      const collectionMint = await createMetadataNft(maker.publicKey);
+      const memberMint = await createMetadataNft(taker.publicKey, {
+        collection: collectionMint,
+      });
+      await verifyCollection(memberMint, collectionMint);
+      const nonMemberMint = await createNFT(taker.publicKey);
+
+      const offeredMint = await createNFT(maker.publicKey);
+      const { offer: swapOffer } = await nextOffer(maker.publicKey, "swap_offer");
+      const swapNftVault = await getAssociatedTokenAddress(
+        offeredMint,
+        swapOffer,
+        true
+      );
+      const topUpVault = await getAssociatedTokenAddress(
+        paymentMint,
+        swapOffer,
+        true
+      );
+
+      // Maker asks for any verified member and escrows 5 tokens for the taker
+      await mintTo(
+        provider.connection,
+        maker,
+        paymentMint,
+        makerPaymentAccount,
+        mintAuthority,
+        Number(topUp)
+      );
+      await program.methods
+        .makeSwapOffer(
+          { collection: [collectionMint] },
+          { amount: topUp, direction: { makerPays: {} } },
+          expiresIn(3600)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: offeredMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: await getAssociatedTokenAddress(
+            offeredMint,
+            maker.publicKey
+          ),
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          swapOffer: swapOffer,
+          nftVault: swapNftVault,
+          topUpVault: topUpVault,
+        })
+        .signers([maker])
+        .rpc();
+      const vaultInfo = await getAccount(provider.connection, topUpVault);
+      assert.equal(vaultInfo.amount.toString(), topUp.toString());
+      console.log("  ✅ Collection swap created with escrowed top-up");
+
+      const takeAccounts = (requestedMint: PublicKey) => ({
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        requestedTokenProgram: TOKEN_PROGRAM_ID,
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        taker: taker.publicKey,
+        maker: maker.publicKey,
+        nftMint: offeredMint,
+        nftMetadata: findMetadataPda(offeredMint),
+        requestedMint: requestedMint,
+        requestedMetadata: findMetadataPda(requestedMint),
+        paymentMint: paymentMint,
+        takerRequestedAccount: getAssociatedTokenAddressSync(
+          requestedMint,
+          taker.publicKey
+        ),
+        makerRequestedAccount: getAssociatedTokenAddressSync(
+          requestedMint,
+          maker.publicKey
+        ),
+        takerNftAccount: getAssociatedTokenAddressSync(
+          offeredMint,
+          taker.publicKey
+        ),
+        takerPaymentAccount: takerPaymentAccount,
+        makerPaymentAccount: null,
+        config: configAccount,
+        treasury: treasury.publicKey,
+        treasuryPaymentAccount: treasuryPaymentAccount,
+        swapOffer: swapOffer,
+        nftVault: swapNftVault,
+        topUpVault: topUpVault,
+      });
+
+      // An NFT outside the collection has no metadata to prove membership
+      try {
+        await program.methods
+          .takeSwapOffer()
+          .accounts(takeAccounts(nonMemberMint))
+          .signers([taker])
+          .rpc();
+
+        assert.fail("Should have thrown error for a non-member NFT");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidMetadata",
+          "Should fail with InvalidMetadata error"
+        );
+        console.log("  ✅ Correctly rejected an NFT outside the collection");
+      }
+
+      const takerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      await program.methods
+        .takeSwapOffer()
+        .accounts(takeAccounts(memberMint))
+        .signers([taker])
+        .rpc();
+
+      // Verify both NFTs changed hands and the taker got the top-up minus the fee
+      const makerMemberInfo = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(memberMint, maker.publicKey)
+      );
+      assert.equal(makerMemberInfo.amount.toString(), "1");
+      const takerOfferedInfo = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(offeredMint, taker.publicKey)
+      );
+      assert.equal(takerOfferedInfo.amount.toString(), "1");
+      const takerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      const topUpFee = (Number(topUp) * feeBps) / 10_000;
+      assert.equal(
+        Number(takerBalanceAfter.amount - takerBalanceBefore.amount),
+        Number(topUp) - topUpFee,
+        "Taker should receive the top-up minus the fee"
+      );
+      const topUpVaultInfo = await provider.connection.getAccountInfo(
+        topUpVault
+      );
+      assert.isNull(topUpVaultInfo, "Top-up vault should be closed");
+      console.log("  ✅ Collection member swapped for the NFT and top-up");
+
+      console.log("\n✅ Collection swap test passed!\n");
+    });
+
+    it("Should refund the NFT and top-up on cancel despite vault dust", async () => {
+      console.log("\n📝 Test: Cancel Swap Offer");
+
+      const topUp = new anchor.BN(5 * 1_000_000);
+      const offeredMint = await createNFT(maker.publicKey);
+      const requestedMint = await createNFT(taker.publicKey);
+      const makerNftAccount = await getAssociatedTokenAddress(
+        offeredMint,
+        maker.publicKey
+      );
+      const { offer: swapOffer } = await nextOffer(maker.publicKey, "swap_offer");
+      const swapNftVault = await getAssociatedTokenAddress(
+        offeredMint,
+        swapOffer,
+        true
+      );
+      const topUpVault = await getAssociatedTokenAddress(
+        paymentMint,
+        swapOffer,
+        true
+      );
+
+      await mintTo(
+        provider.connection,
+        maker,
+        paymentMint,
+        makerPaymentAccount,
+        mintAuthority,
+        Number(topUp)
+      );
+      const makerBalanceBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      await program.methods
+        .makeSwapOffer(
+          { mint: [requestedMint] },
+          { amount: topUp, direction: { makerPays: {} } },
+          expiresIn(3600)
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: offeredMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: makerNftAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          swapOffer: swapOffer,
+          nftVault: swapNftVault,
+          topUpVault: topUpVault,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Anyone can send a stray token to the public top-up vault
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        topUpVault,
+        mintAuthority,
+        1
+      );
+
+      await program.methods
+        .cancelSwapOffer()
+        .accounts({
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: offeredMint,
+          paymentMint: paymentMint,
+          makerNftAccount: makerNftAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          swapOffer: swapOffer,
+          nftVault: swapNftVault,
+          topUpVault: topUpVault,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Verify the NFT and the full top-up were returned to the maker
+      const makerNftInfo = await getAccount(
+        provider.connection,
+        makerNftAccount
+      );
+      assert.equal(makerNftInfo.amount.toString(), "1");
+      const makerBalanceAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      assert.equal(
+        makerBalanceAfter.amount.toString(),
+        makerBalanceBefore.amount.toString(),
+        "Maker should get the top-up back"
+      );
+      const swapOfferInfo = await provider.connection.getAccountInfo(swapOffer);
+      assert.isNull(swapOfferInfo, "Swap offer should be closed");
+      const topUpVaultInfo = await getAccount(provider.connection, topUpVault);
+      assert.equal(topUpVaultInfo.amount.toString(), "1");
+      console.log("  ✅ NFT and top-up refunded, dusted vault left open");
+
+      console.log("\n✅ Cancel swap offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2f: Bundle listing of several NFTs
+   */
+  describe("Bundle Listing", () => {
+    it("Should sell every NFT of a bundle in one take", async () => {
+      console.log("\n📝 Test: Bundle Listing");
+
+      const bundleMints = [
+        await createNFT(maker.publicKey),
+        await createNFT(maker.publicKey),
+        await createNFT(maker.publicKey),
+      ];
+      const [primaryMint, ...extraMints] = bundleMints;
+      const { offer: bundleOffer } = await nextOffer(maker.publicKey);
+      const vaults = await Promise.all(
+        bundleMints.map((mint) =>
+          getAssociatedTokenAddress(mint, bundleOffer, true)
+        )
+      );
+
+      // Extra NFTs: (mint, maker NFT account, vault) each
+      const makeRemainingAccounts = [];
+      for (let i = 1; i < bundleMints.length; i++) {
+        makeRemainingAccounts.push(
+          { pubkey: bundleMints[i], isSigner: false, isWritable: false },
+          {
+            pubkey: await getAssociatedTokenAddress(
+              bundleMints[i],
+              maker.publicKey
+            ),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: vaults[i], isSigner: false, isWritable: true }
+        );
+      }
+
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: primaryMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: await getAssociatedTokenAddress(
+            primaryMint,
+            maker.publicKey
+          ),
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: bundleOffer,
+          nftVault: vaults[0],
+          pnft: noPnft,
+        })
+        .remainingAccounts(makeRemainingAccounts)
+        .signers([maker])
+        .rpc();
+
+      const offerInfo = await program.account.nftOffer.fetch(bundleOffer);
+      assert.equal(offerInfo.bundleMints.length, extraMints.length);
+      console.log("  ✅ Bundle of", bundleMints.length, "NFTs listed");
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+
+      // Extra NFTs: (mint, vault, taker NFT account) each
+      const takerNftAccounts = await Promise.all(
+        bundleMints.map((mint) =>
+          getAssociatedTokenAddress(mint, taker.publicKey)
+        )
+      );
+      const takeRemainingAccounts = [];
+      for (let i = 1; i < bundleMints.length; i++) {
+        takeRemainingAccounts.push(
+          { pubkey: bundleMints[i], isSigner: false, isWritable: false },
+          { pubkey: vaults[i], isSigner: false, isWritable: true },
+          { pubkey: takerNftAccounts[i], isSigner: false, isWritable: true }
+        );
+      }
+
+      await program.methods
+        .takeOffer(paymentAmount, paymentMint, { token: {} })
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: primaryMint,
+          nftMetadata: findMetadataPda(primaryMint),
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          takerNftAccount: takerNftAccounts[0],
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: bundleOffer,
+          nftVault: vaults[0],
+          pnft: noPnft,
+        })
+        .remainingAccounts(takeRemainingAccounts)
+        .signers([taker])
+        .rpc();
+
+      // Verify taker received every NFT of the bundle
+      for (const account of takerNftAccounts) {
+        const info = await getAccount(provider.connection, account);
+        assert.equal(info.amount.toString(), "1");
+      }
+      console.log("  ✅ Taker received the whole bundle");
+
+      console.log("\n✅ Bundle listing test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2g: Sweep several offers in one transaction
+   */
+  describe("Batch Take", () => {
+    // Maker lists `count` NFTs at the same price
+    const listBatch = async (count: number) => {
+      const listings = [];
+      for (let i = 0; i < count; i++) {
+        const mint = await createNFT(maker.publicKey);
+        const { offer } = await nextOffer(maker.publicKey);
+        const vault = await getAssociatedTokenAddress(mint, offer, true);
+        await program.methods
+          .makeOffer(paymentAmount, expiresIn(3600), null, null)
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            maker: maker.publicKey,
+            nftMint: mint,
+            nftMetadata: null,
+            paymentMint: paymentMint,
+            makerNftAccount: await getAssociatedTokenAddress(
+              mint,
+              maker.publicKey
+            ),
+            config: configAccount,
+            makerProfile: makerProfile,
+            offer: offer,
+            nftVault: vault,
+            pnft: noPnft,
+          })
+          .signers([maker])
+          .rpc();
+        listings.push({ mint, offer, vault });
+      }
+      return listings;
+    };
+
+    // Per offer: offer, maker, nftMint, nftVault, nftMetadata,
+    // takerNftAccount, makerPaymentAccount (no royalty creators)
+    const batchAccounts = async (listings, makerKey = maker.publicKey) => {
+      const remainingAccounts = [];
+      for (const { mint, offer, vault } of listings) {
+        remainingAccounts.push(
+          { pubkey: offer, isSigner: false, isWritable: true },
+          { pubkey: makerKey, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: vault, isSigner: false, isWritable: true },
+          { pubkey: findMetadataPda(mint), isSigner: false, isWritable: false },
+          {
+            pubkey: await getAssociatedTokenAddress(mint, taker.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: makerPaymentAccount, isSigner: false, isWritable: true }
+        );
+      }
+      return remainingAccounts;
+    };
+
+    const takeBatch = (
+      listings,
+      remainingAccounts,
+      maxTotalSpend: anchor.BN,
+      allOrNothing: boolean
+    ) =>
+      program.methods
+        .takeOffersBatch(
+          listings.map(() => ({
+            expectedTokenAmount: paymentAmount,
+            creatorCount: 0,
+          })),
+          maxTotalSpend,
+          allOrNothing
+        )
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([taker])
+        .preInstructions([
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+            units: 600_000,
+          }),
+        ])
+        .rpc();
+
+    it("Should fill offers up to the spend cap and skip the rest", async () => {
+      console.log("\n📝 Test: Batch Take");
+
+      const listings = await listBatch(3);
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount) * 3
+      );
+
+      // Budget only covers two of the three offers (best-effort fill)
+      await takeBatch(
+        listings,
+        await batchAccounts(listings),
+        paymentAmount.muln(2),
+        false
+      );
+
+      // First two offers filled and closed, the third is still listed
+      for (const { offer } of listings.slice(0, 2)) {
+        const info = await provider.connection.getAccountInfo(offer);
+        assert.isNull(info, "Filled offer should be closed");
+      }
+      const skipped = await program.account.nftOffer.fetch(listings[2].offer);
+      assert.ok(skipped.maker.equals(maker.publicKey));
+      console.log("  ✅ Two offers filled, third skipped at the spend cap");
+
+      console.log("\n✅ Batch take test passed!\n");
+    });
+
+    it("Should fail the whole batch when all-or-nothing or malformed", async () => {
+      console.log("\n📝 Test: Batch Take (all-or-nothing)");
+
+      const listings = await listBatch(2);
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount) * 2
+      );
+
+      // Budget only covers one of the two offers
+      try {
+        await takeBatch(
+          listings,
+          await batchAccounts(listings),
+          paymentAmount,
+          true
+        );
+
+        assert.fail("Should have thrown error for an all-or-nothing batch");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "SpendCapExceeded",
+          "Should fail with SpendCapExceeded error"
+        );
+        console.log("  ✅ All-or-nothing batch rejected over budget");
+      }
+
+      // Wrong maker accounts fail even a best-effort batch
+      try {
+        await takeBatch(
+          listings,
+          await batchAccounts(listings, taker.publicKey),
+          paymentAmount.muln(2),
+          false
+        );
+
+        assert.fail("Should have thrown error for malformed batch accounts");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidBatchAccounts",
+          "Should fail with InvalidBatchAccounts error"
+        );
+        console.log("  ✅ Best-effort batch rejected malformed accounts");
+      }
+
+      // Neither batch filled anything
+      for (const { offer } of listings) {
+        const listed = await program.account.nftOffer.fetch(offer);
+        assert.ok(listed.maker.equals(maker.publicKey));
+      }
+      console.log("  ✅ Both offers are still listed");
+
+      console.log("\n✅ Batch take all-or-nothing test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2h: Non-custodial delegate listing
+   */
+  describe("Delegate Listing", () => {
+    it("Should sell an NFT that stayed in the maker's wallet", async () => {
+      console.log("\n📝 Test: Delegate Listing");
+
+      const delegateMint = await createNFT(maker.publicKey);
+      const delegateMakerNftAccount = await getAssociatedTokenAddress(
+        delegateMint,
+        maker.publicKey
+      );
+      const { offer: delegateOffer } = await nextOffer(maker.publicKey);
+
+      await program.methods
+        .makeDelegateOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: delegateMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: delegateMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: delegateOffer,
+          nftVault: null,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Verify NFT is still in the maker's wallet with the offer as delegate
+      const listedInfo = await getAccount(
+        provider.connection,
+        delegateMakerNftAccount
+      );
+      assert.equal(listedInfo.amount.toString(), "1");
+      assert.ok(listedInfo.delegate.equals(delegateOffer));
+      console.log("  ✅ NFT listed without leaving the maker's wallet");
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+
+      const delegateTakerNftAccount = await getAssociatedTokenAddress(
+        delegateMint,
+        taker.publicKey
+      );
+      await program.methods
+        .takeOffer(paymentAmount, paymentMint, { token: {} })
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: delegateMint,
+          nftMetadata: findMetadataPda(delegateMint),
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          takerNftAccount: delegateTakerNftAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: delegateOffer,
+          nftVault: null,
+          makerNftAccount: delegateMakerNftAccount,
+          pnft: noPnft,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify NFT moved from the maker's wallet to the taker
+      const takerNftInfo = await getAccount(
+        provider.connection,
+        delegateTakerNftAccount
+      );
+      assert.equal(takerNftInfo.amount.toString(), "1");
+      const makerNftInfo = await getAccount(
+        provider.connection,
+        delegateMakerNftAccount
+      );
+      assert.equal(makerNftInfo.amount.toString(), "0");
+      console.log("  ✅ Taker received NFT via the delegate");
+
+      console.log("\n✅ Delegate listing test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2i: MPL Core asset offers
+   */
+  describe("Core Asset Offer", () => {
+    it("Should escrow an MPL Core asset and sell it", async () => {
+      console.log("\n📝 Test: Core Asset Offer");
+
+      const asset = await createCoreAsset(maker.publicKey);
+      const { offer: coreOffer } = await nextOffer(
+        maker.publicKey,
+        "core_offer"
+      );
+
+      await program.methods
+        .makeCoreOffer(paymentAmount, expiresIn(3600), null)
+        .accounts({
+          mplCoreProgram: MPL_CORE_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          asset: asset,
+          collection: null,
+          paymentMint: paymentMint,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: coreOffer,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Verify the offer PDA now owns the asset
+      assert.ok((await coreAssetOwner(asset)).equals(coreOffer));
+      console.log("  ✅ Asset escrowed by the offer PDA");
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      const makerBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+
+      await program.methods
+        .takeCoreOffer(paymentAmount, paymentMint, { token: {} })
+        .accounts({
+          mplCoreProgram: MPL_CORE_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          asset: asset,
+          collection: null,
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: coreOffer,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify taker owns the asset and maker was paid minus the fee
+      assert.ok((await coreAssetOwner(asset)).equals(taker.publicKey));
+      const makerAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      assert.equal(
+        (makerAfter.amount - makerBefore.amount).toString(),
+        (Number(paymentAmount) - feeAmount).toString()
+      );
+      console.log("  ✅ Taker received the asset, maker was paid");
+
+      console.log("\n✅ Core asset offer test passed!\n");
+    });
+
+    it("Should return the asset when a Core offer is cancelled", async () => {
+      console.log("\n📝 Test: Cancel Core Asset Offer");
+
+      const asset = await createCoreAsset(maker.publicKey);
+      const { offer: coreOffer } = await nextOffer(
+        maker.publicKey,
+        "core_offer"
+      );
+
+      await program.methods
+        .makeCoreOffer(paymentAmount, expiresIn(3600), null)
+        .accounts({
+          mplCoreProgram: MPL_CORE_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          asset: asset,
+          collection: null,
+          paymentMint: null,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: coreOffer,
+        })
+        .signers([maker])
+        .rpc();
+
+      await program.methods
+        .cancelCoreOffer()
+        .accounts({
+          mplCoreProgram: MPL_CORE_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          asset: asset,
+          collection: null,
+          offer: coreOffer,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Verify the asset is back with the maker and the offer is closed
+      assert.ok((await coreAssetOwner(asset)).equals(maker.publicKey));
+      const offerInfo = await provider.connection.getAccountInfo(coreOffer);
+      assert.isNull(offerInfo);
+      console.log("  ✅ Asset returned to maker");
+
+      console.log("\n✅ Cancel Core asset offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2j: Programmable NFT offers through a real rule set
+   */
+  describe("Programmable NFT", () => {
+    let ruleSet: PublicKey;
+
+    before(async () => {
+      ruleSet = await createRuleSet("nft_escrow_pass_all");
+    });
+
+    it("Should list and sell a pNFT through its rule set", async () => {
+      console.log("\n📝 Test: Programmable NFT Offer");
+
+      const pnftMint = await createMetadataNft(maker.publicKey, { ruleSet });
+      const makerPnftAccount = await getAssociatedTokenAddress(
+        pnftMint,
+        maker.publicKey
+      );
+      const { offer, makerProfile: profile } = await nextOffer(
+        maker.publicKey
+      );
+      const pnftVault = await getAssociatedTokenAddress(pnftMint, offer, true);
+      const takerPnftAccount = await getAssociatedTokenAddress(
+        pnftMint,
+        taker.publicKey
+      );
+      const computeBudget = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit(
+        { units: 400_000 }
+      );
+
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: pnftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: makerPnftAccount,
+          config: configAccount,
+          makerProfile: profile,
+          offer: offer,
+          nftVault: pnftVault,
+          pnft: pnftAccounts(pnftMint, makerPnftAccount, pnftVault, ruleSet),
+        })
+        .signers([maker])
+        .preInstructions([computeBudget])
+        .rpc();
+
+      // Token Metadata keeps the escrowed pNFT frozen in the vault
+      const vaultInfo = await getAccount(provider.connection, pnftVault);
+      assert.equal(vaultInfo.amount.toString(), "1");
+      assert.isTrue(vaultInfo.isFrozen, "pNFT vault should be frozen");
+      console.log("  ✅ pNFT escrowed");
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      await program.methods
+        .takeOffer(paymentAmount, paymentMint, { token: {} })
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          nftMint: pnftMint,
+          nftMetadata: findMetadataPda(pnftMint),
+          paymentMint: paymentMint,
+          takerPaymentAccount: takerPaymentAccount,
+          takerNftAccount: takerPnftAccount,
+          makerPaymentAccount: makerPaymentAccount,
+          config: configAccount,
+          treasury: treasury.publicKey,
+          treasuryPaymentAccount: treasuryPaymentAccount,
+          offer: offer,
+          nftVault: pnftVault,
+          pnft: pnftAccounts(pnftMint, pnftVault, takerPnftAccount, ruleSet),
+        })
+        .signers([taker])
+        .preInstructions([computeBudget])
+        .rpc();
+
+      // Verify taker holds the (frozen) pNFT and the vault and offer are closed
+      const takerPnftInfo = await getAccount(
+        provider.connection,
+        takerPnftAccount
+      );
+      assert.equal(takerPnftInfo.amount.toString(), "1");
+      assert.isTrue(takerPnftInfo.isFrozen, "pNFT should stay frozen");
+      assert.isNull(
+        await provider.connection.getAccountInfo(pnftVault),
+        "pNFT vault should be closed"
+      );
+      assert.isNull(
+        await provider.connection.getAccountInfo(offer),
+        "Offer account should be closed"
+      );
+      console.log("  ✅ Taker received pNFT");
+
+      console.log("\n✅ Programmable NFT offer test passed!\n");
+    });
+
+    it("Should return a pNFT to the maker on cancel", async () => {
+      console.log("\n📝 Test: Cancel Programmable NFT Offer");
+
+      const pnftMint = await createMetadataNft(maker.publicKey, { ruleSet });
+      const makerPnftAccount = await getAssociatedTokenAddress(
+        pnftMint,
+        maker.publicKey
+      );
+      const { offer, makerProfile: profile } = await nextOffer(
+        maker.publicKey
+      );
+      const pnftVault = await getAssociatedTokenAddress(pnftMint, offer, true);
+      const computeBudget = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit(
+        { units: 400_000 }
+      );
+
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: pnftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: makerPnftAccount,
+          config: configAccount,
+          makerProfile: profile,
+          offer: offer,
+          nftVault: pnftVault,
+          pnft: pnftAccounts(pnftMint, makerPnftAccount, pnftVault, ruleSet),
+        })
+        .signers([maker])
+        .preInstructions([computeBudget])
+        .rpc();
+
+      await program.methods
+        .cancelOffer()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: pnftMint,
+          makerNftAccount: makerPnftAccount,
+          offer: offer,
+          nftVault: pnftVault,
+          pnft: pnftAccounts(pnftMint, pnftVault, makerPnftAccount, ruleSet),
+        })
+        .signers([maker])
+        .preInstructions([computeBudget])
+        .rpc();
+
+      // Verify the pNFT is back with the maker and the vault is closed
+      const makerPnftInfo = await getAccount(
+        provider.connection,
+        makerPnftAccount
+      );
+      assert.equal(makerPnftInfo.amount.toString(), "1");
+      assert.isNull(
+        await provider.connection.getAccountInfo(pnftVault),
+        "pNFT vault should be closed"
+      );
+      console.log("  ✅ pNFT returned to maker");
+
+      console.log("\n✅ Cancel programmable NFT offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 2k: Royalties paid to the verified creators of a Token Metadata NFT
+   */
+  describe("Creator Royalties", () => {
+    it("Should pay verified creators their share and skip unverified ones", async () => {
+      console.log("\n📝 Test: Creator Royalties");
+
+      // 5% royalties split 70/30; only the first creator verifies
+      const verifiedCreator = Keypair.generate();
+      const unverifiedCreator = Keypair.generate();
+      const royaltyMint = await createMetadataNft(maker.publicKey, {
+        sellerFeeBasisPoints: 500,
+        creators: [
+          { address: verifiedCreator.publicKey, share: 70 },
+          { address: unverifiedCreator.publicKey, share: 30 },
+        ],
+      });
+      await verifyCreator(royaltyMint, verifiedCreator);
+
+      const verifiedCreatorAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        maker,
+        paymentMint,
+        verifiedCreator.publicKey
+      );
+      const unverifiedCreatorAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        maker,
+        paymentMint,
+        unverifiedCreator.publicKey
+      );
+
+      const makerRoyaltyNftAccount = await getAssociatedTokenAddress(
+        royaltyMint,
+        maker.publicKey
+      );
+      const { offer, makerProfile: profile } = await nextOffer(
+        maker.publicKey
+      );
+      const royaltyVault = await getAssociatedTokenAddress(
+        royaltyMint,
+        offer,
+        true
+      );
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: royaltyMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: makerRoyaltyNftAccount,
+          config: configAccount,
+          makerProfile: profile,
+          offer: offer,
+          nftVault: royaltyVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(paymentAmount)
+      );
+      const takeOffer = (creatorAccounts: PublicKey[]) =>
+        program.methods
+          .takeOffer(paymentAmount, paymentMint, { token: {} })
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            taker: taker.publicKey,
+            maker: maker.publicKey,
+            nftMint: royaltyMint,
+            nftMetadata: findMetadataPda(royaltyMint),
+            paymentMint: paymentMint,
+            takerPaymentAccount: takerPaymentAccount,
+            takerNftAccount: await getAssociatedTokenAddress(
+              royaltyMint,
+              taker.publicKey
+            ),
+            makerPaymentAccount: makerPaymentAccount,
+            config: configAccount,
+            treasury: treasury.publicKey,
+            treasuryPaymentAccount: treasuryPaymentAccount,
+            offer: offer,
+            nftVault: royaltyVault,
+            pnft: noPnft,
+          })
+          .remainingAccounts(
+            creatorAccounts.map((pubkey) => ({
+              pubkey,
+              isSigner: false,
+              isWritable: true,
+            }))
+          )
+          .signers([taker])
+          .rpc();
+
+      // The verified creator's payment account must be passed
+      try {
+        await takeOffer([]);
+        assert.fail("Should have thrown error for a missing creator account");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "MissingCreatorAccount",
+          "Should fail with MissingCreatorAccount error"
+        );
+        console.log("  ✅ Correctly rejected a missing creator account");
+      }
+
+      // ...and it must be that creator's ATA
+      try {
+        await takeOffer([unverifiedCreatorAccount]);
+        assert.fail("Should have thrown error for a wrong creator account");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "InvalidCreatorAccount",
+          "Should fail with InvalidCreatorAccount error"
+        );
+        console.log("  ✅ Correctly rejected another creator's account");
+      }
+
+      const makerBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      const treasuryBefore = await getAccount(
+        provider.connection,
+        treasuryPaymentAccount
+      );
+      await takeOffer([verifiedCreatorAccount]);
+
+      // Royalty pool 5 tokens: 70% to the verified creator, none to the other
+      const price = Number(paymentAmount);
+      const verifiedRoyalty = (price * 500 * 70) / 10_000 / 100;
+      const verifiedCreatorInfo = await getAccount(
+        provider.connection,
+        verifiedCreatorAccount
+      );
+      assert.equal(
+        verifiedCreatorInfo.amount.toString(),
+        verifiedRoyalty.toString(),
+        "Verified creator should receive their share"
+      );
+      const unverifiedCreatorInfo = await getAccount(
+        provider.connection,
+        unverifiedCreatorAccount
+      );
+      assert.equal(
+        unverifiedCreatorInfo.amount.toString(),
+        "0",
+        "Unverified creator should receive nothing"
+      );
+
+      // Fee to treasury, the rest to the maker
+      const makerAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      const treasuryAfter = await getAccount(
+        provider.connection,
+        treasuryPaymentAccount
+      );
+      assert.equal(
+        (treasuryAfter.amount - treasuryBefore.amount).toString(),
+        feeAmount.toString(),
+        "Treasury should receive the fee"
+      );
+      assert.equal(
+        (makerAfter.amount - makerBefore.amount).toString(),
+        (price - feeAmount - verifiedRoyalty).toString(),
+        "Maker should receive the price less fee and royalties"
+      );
+      console.log("  ✅ Fee, royalties and maker proceeds split correctly");
+
+      console.log("\n✅ Creator royalties test passed!\n");
+    });
+  });
+
+  /**
+   * Test 3: Successfully cancel an NFT offer
+   */
+  describe("Cancel Offer", () => {
+    it("Should successfully cancel an NFT offer and return NFT", async () => {
+      console.log("\n📝 Test: Cancel NFT Offer");
+
+      // Create a new offer to cancel
+      const cancelNftMint = await createNFT(maker.publicKey);
+      const cancelMakerNftAccount = await getAssociatedTokenAddress(
+        cancelNftMint,
+        maker.publicKey
+      );
+
+      const { offer: cancelOfferAccount } = await nextOffer(maker.publicKey);
+
+      const cancelNftVault = await getAssociatedTokenAddress(
+        cancelNftMint,
+        cancelOfferAccount,
+        true
+      );
+
+      // Create the offer
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: cancelNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: cancelMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: cancelOfferAccount,
+          nftVault: cancelNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      console.log("  Created offer to cancel");
+
+      // Verify vault has NFT before cancellation
+      const vaultBefore = await getAccount(
+        provider.connection,
+        cancelNftVault
+      );
+      assert.equal(vaultBefore.amount.toString(), "1");
+      console.log("  ✅ Vault has NFT before cancellation");
+
+      // Cancel the offer
+      const tx = await program.methods
+        .cancelOffer()
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: cancelNftMint,
+          makerNftAccount: cancelMakerNftAccount,
+          offer: cancelOfferAccount,
+          nftVault: cancelNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      console.log("  Transaction:", tx);
+
+      // Verify maker got NFT back
+      const makerNftAfter = await getAccount(
+        provider.connection,
+        cancelMakerNftAccount
+      );
+      assert.equal(
+        makerNftAfter.amount.toString(),
+        "1",
+        "Maker should have NFT back"
+      );
+      console.log("  ✅ Maker received NFT back");
+
+      // Verify vault is closed
+      try {
+        await getAccount(provider.connection, cancelNftVault);
+        assert.fail("Vault should be closed");
+      } catch (error) {
+        console.log("  ✅ Vault closed successfully");
+      }
+
+      // Verify offer account is closed
+      try {
+        await program.account.nftOffer.fetch(cancelOfferAccount);
+        assert.fail("Offer account should be closed");
+      } catch (error) {
+        console.log("  ✅ Offer account closed successfully");
+      }
+
+      console.log("\n✅ Cancel offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 4: Reprice an open offer in place
+   */
+  describe("Update Offer", () => {
+    it("Should reprice an offer without moving the NFT", async () => {
+      console.log("\n📝 Test: Update NFT Offer");
+
+      const updateNftMint = await createNFT(maker.publicKey);
+      const updateMakerNftAccount = await getAssociatedTokenAddress(
+        updateNftMint,
+        maker.publicKey
+      );
+      const { offer: updateOfferAccount } = await nextOffer(maker.publicKey);
+      const updateNftVault = await getAssociatedTokenAddress(
+        updateNftMint,
+        updateOfferAccount,
+        true
+      );
+
+      // Create the offer
+      await program.methods
+        .makeOffer(paymentAmount, expiresIn(3600), null, null)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          maker: maker.publicKey,
+          nftMint: updateNftMint,
+          nftMetadata: null,
+          paymentMint: paymentMint,
+          makerNftAccount: updateMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: updateOfferAccount,
+          nftVault: updateNftVault,
+          pnft: noPnft,
+        })
+        .signers([maker])
+        .rpc();
+
+      // Reprice in a different payment token
+      const newPaymentMint = await createToken();
+      const newAmount = new anchor.BN(42 * 1_000_000);
+      await program.methods
+        .updateOffer(newAmount, { token: {} })
+        .accounts({
+          tokenProgram: TOKEN_PROGRAM_ID,
+          maker: maker.publicKey,
+          newPaymentMint: newPaymentMint,
+          offer: updateOfferAccount,
+        })
+        .signers([maker])
+        .rpc();
+
+      const offerInfo = await program.account.nftOffer.fetch(
+        updateOfferAccount
       );
       assert.equal(
-        makerPaymentAccountInfo.amount.toString(),
-        paymentAmount.toString(),
-        "Maker should receive payment"
+        offerInfo.tokenAmount.toString(),
+        newAmount.toString(),
+        "Token amount should be updated"
       );
-      console.log("  ✅ Maker received payment");
+      assert.equal(
+        offerInfo.paymentMint.toString(),
+        newPaymentMint.toString(),
+        "Payment mint should be updated"
+      );
+      console.log("  ✅ Offer repriced");
 
-      // Verify vault is closed
+      // Native SOL mode takes no payment mint
       try {
-        await getAccount(provider.connection, nftVault);
-        assert.fail("Vault should be closed");
+        await program.methods
+          .updateOffer(newAmount, { nativeSol: {} })
+          .accounts({
+            tokenProgram: TOKEN_PROGRAM_ID,
+            maker: maker.publicKey,
+            newPaymentMint: newPaymentMint,
+            offer: updateOfferAccount,
+          })
+          .signers([maker])
+          .rpc();
+
+        assert.fail("Should have thrown error for payment mode mismatch");
       } catch (error) {
-        console.log("  ✅ Vault closed successfully");
+        assert.include(
+          error.toString(),
+          "PaymentModeMismatch",
+          "Should fail with PaymentModeMismatch error"
+        );
+        console.log("  ✅ Correctly rejected a mint for native SOL mode");
       }
 
-      // Verify offer account is closed
+      // Switch back to native SOL
+      await program.methods
+        .updateOffer(newAmount, { nativeSol: {} })
+        .accounts({
+          tokenProgram: TOKEN_PROGRAM_ID,
+          maker: maker.publicKey,
+          newPaymentMint: null,
+          offer: updateOfferAccount,
+        })
+        .signers([maker])
+        .rpc();
+
+      const nativeOfferInfo = await program.account.nftOffer.fetch(
+        updateOfferAccount
+      );
+      assert.deepEqual(
+        nativeOfferInfo.paymentMode,
+        { nativeSol: {} },
+        "Offer should be priced in native SOL"
+      );
+      assert.equal(
+        nativeOfferInfo.paymentMint.toString(),
+        NATIVE_MINT.toString(),
+        "Payment mint should be the native mint"
+      );
+      console.log("  ✅ Offer switched to native SOL");
+
+      // Going back to tokens needs a mint
       try {
-        await program.account.nftOffer.fetch(offerAccount);
-        assert.fail("Offer account should be closed");
+        await program.methods
+          .updateOffer(newAmount, { token: {} })
+          .accounts({
+            tokenProgram: TOKEN_PROGRAM_ID,
+            maker: maker.publicKey,
+            newPaymentMint: null,
+            offer: updateOfferAccount,
+          })
+          .signers([maker])
+          .rpc();
+
+        assert.fail("Should have thrown error for missing payment mint");
       } catch (error) {
-        console.log("  ✅ Offer account closed successfully");
+        assert.include(
+          error.toString(),
+          "MissingPaymentAccount",
+          "Should fail with MissingPaymentAccount error"
+        );
+        console.log("  ✅ Correctly required a mint for token mode");
       }
 
-      console.log("\n✅ Take offer test passed!\n");
+      // NFT never left the vault
+      const vaultInfo = await getAccount(provider.connection, updateNftVault);
+      assert.equal(vaultInfo.amount.toString(), "1", "Vault should keep NFT");
+      console.log("  ✅ NFT still escrowed");
+
+      console.log("\n✅ Update offer test passed!\n");
     });
   });
 
   /**
-   * Test 3: Successfully cancel an NFT offer
+   * Test 5: Expired offers can be closed by anyone
    */
-  describe("Cancel Offer", () => {
-    it("Should successfully cancel an NFT offer and return NFT", async () => {
-      console.log("\n📝 Test: Cancel NFT Offer");
+  describe("Close Expired Offer", () => {
+    it("Should let anyone return an expired offer's NFT to the maker", async () => {
+      console.log("\n📝 Test: Close Expired Offer");
 
-      // Create a new offer to cancel
-      const cancelOfferId = new anchor.BN(3);
-      const cancelNftMint = await createNFT(maker.publicKey);
-      const cancelMakerNftAccount = await getAssociatedTokenAddress(
-        cancelNftMint,
+      const expiredNftMint = await createNFT(maker.publicKey);
+      const expiredMakerNftAccount = await getAssociatedTokenAddress(
+        expiredNftMint,
         maker.publicKey
       );
 
-      const [cancelOfferAccount] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("nft_offer"),
-          cancelOfferId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
-      );
+      const { offer: expiredOfferAccount } = await nextOffer(maker.publicKey);
 
-      const cancelNftVault = await getAssociatedTokenAddress(
-        cancelNftMint,
-        cancelOfferAccount,
+      const expiredNftVault = await getAssociatedTokenAddress(
+        expiredNftMint,
+        expiredOfferAccount,
         true
       );
 
-      // Create the offer
+      // Create an offer that expires almost immediately
       await program.methods
-        .makeOffer(cancelOfferId, paymentAmount)
+        .makeOffer(paymentAmount, expiresIn(2), null, null)
         .accounts({
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          nftTokenProgram: TOKEN_PROGRAM_ID,
+          paymentTokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           maker: maker.publicKey,
-          nftMint: cancelNftMint,
+          nftMint: expiredNftMint,
+          nftMetadata: null,
           paymentMint: paymentMint,
-          makerNftAccount: cancelMakerNftAccount,
-          offer: cancelOfferAccount,
-          nftVault: cancelNftVault,
+          makerNftAccount: expiredMakerNftAccount,
+          config: configAccount,
+          makerProfile: makerProfile,
+          offer: expiredOfferAccount,
+          nftVault: expiredNftVault,
+          pnft: noPnft,
         })
         .signers([maker])
         .rpc();
 
-      console.log("  Created offer to cancel");
+      // Wait for the offer to expire
+      await new Promise((resolve) => setTimeout(resolve, 4000));
 
-      // Verify vault has NFT before cancellation
-      const vaultBefore = await getAccount(
-        provider.connection,
-        cancelNftVault
-      );
-      assert.equal(vaultBefore.amount.toString(), "1");
-      console.log("  ✅ Vault has NFT before cancellation");
+      // Taking the expired offer must fail
+      try {
+        await program.methods
+          .takeOffer(paymentAmount, paymentMint, { token: {} })
+          .accounts({
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            nftTokenProgram: TOKEN_PROGRAM_ID,
+            paymentTokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            taker: taker.publicKey,
+            maker: maker.publicKey,
+            nftMint: expiredNftMint,
+            nftMetadata: findMetadataPda(expiredNftMint),
+            paymentMint: paymentMint,
+            takerPaymentAccount: takerPaymentAccount,
+            takerNftAccount: await getAssociatedTokenAddress(
+              expiredNftMint,
+              taker.publicKey
+            ),
+            makerPaymentAccount: makerPaymentAccount,
+            config: configAccount,
+            treasury: treasury.publicKey,
+            treasuryPaymentAccount: treasuryPaymentAccount,
+            offer: expiredOfferAccount,
+            nftVault: expiredNftVault,
+            pnft: noPnft,
+          })
+          .signers([taker])
+          .rpc();
 
-      // Cancel the offer
-      const tx = await program.methods
-        .cancelOffer()
+        assert.fail("Should have thrown error for expired offer");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "OfferExpired",
+          "Should fail with OfferExpired error"
+        );
+        console.log("  ✅ Correctly rejected expired offer");
+      }
+
+      // A third party (taker) cranks the cleanup
+      await program.methods
+        .closeExpiredOffer()
         .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          caller: taker.publicKey,
           maker: maker.publicKey,
-          nftMint: cancelNftMint,
-          makerNftAccount: cancelMakerNftAccount,
-          offer: cancelOfferAccount,
-          nftVault: cancelNftVault,
+          nftMint: expiredNftMint,
+          makerNftAccount: expiredMakerNftAccount,
+          offer: expiredOfferAccount,
+          nftVault: expiredNftVault,
+          pnft: noPnft,
         })
-        .signers([maker])
+        .signers([taker])
         .rpc();
 
-      console.log("  Transaction:", tx);
-
       // Verify maker got NFT back
       const makerNftAfter = await getAccount(
         provider.connection,
-        cancelMakerNftAccount
+        expiredMakerNftAccount
       );
       assert.equal(
         makerNftAfter.amount.toString(),
@@ -516,23 +4636,267 @@ describe("NFT Escrow Tests", () => {
       );
       console.log("  ✅ Maker received NFT back");
 
-      // Verify vault is closed
+      // Verify offer account is closed
       try {
-        await getAccount(provider.connection, cancelNftVault);
-        assert.fail("Vault should be closed");
+        await program.account.nftOffer.fetch(expiredOfferAccount);
+        assert.fail("Offer account should be closed");
       } catch (error) {
-        console.log("  ✅ Vault closed successfully");
+        console.log("  ✅ Offer account closed successfully");
       }
 
-      // Verify offer account is closed
+      console.log("\n✅ Close expired offer test passed!\n");
+    });
+  });
+
+  /**
+   * Test 6: Collection-wide bids
+   */
+  describe("Collection Bids", () => {
+    it("Should escrow a collection bid and refund it on cancel", async () => {
+      console.log("\n📝 Test: Make and Cancel Collection Bid");
+
+      const bidAmount = new anchor.BN(50 * 1_000_000); // 50 tokens
+      const collectionMint = await createNFT(maker.publicKey);
+
+      // Bid ID is allocated by the buyer's profile
+      const {
+        makerProfile: buyerProfile,
+        offerId: bidId,
+        offer: bidAccount,
+      } = await nextOffer(taker.publicKey, "collection_bid");
+      const bidVault = await getAssociatedTokenAddress(
+        paymentMint,
+        bidAccount,
+        true // Allow PDA
+      );
+
+      // Top up taker so they can act as the buyer
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(bidAmount)
+      );
+      const buyerBalanceBefore = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+
+      // Place the bid
+      await program.methods
+        .makeBid(bidAmount)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          buyer: taker.publicKey,
+          collectionMint: collectionMint,
+          paymentMint: paymentMint,
+          buyerPaymentAccount: takerPaymentAccount,
+          makerProfile: buyerProfile,
+          bid: bidAccount,
+          bidVault: bidVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify bid account and escrowed payment
+      const bidInfo = await program.account.collectionBid.fetch(bidAccount);
+      assert.equal(
+        bidInfo.bidId.toString(),
+        bidId.toString(),
+        "Bid ID should match"
+      );
+      assert.equal(
+        bidInfo.collectionMint.toString(),
+        collectionMint.toString(),
+        "Collection mint should match"
+      );
+      assert.equal(
+        bidInfo.tokenAmount.toString(),
+        bidAmount.toString(),
+        "Bid amount should match"
+      );
+      const vaultInfo = await getAccount(provider.connection, bidVault);
+      assert.equal(
+        vaultInfo.amount.toString(),
+        bidAmount.toString(),
+        "Vault should hold the bid amount"
+      );
+      console.log("  ✅ Bid payment escrowed in vault");
+
+      // Cancel the bid
+      await program.methods
+        .cancelBid()
+        .accounts({
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          buyer: taker.publicKey,
+          paymentMint: paymentMint,
+          buyerPaymentAccount: takerPaymentAccount,
+          bid: bidAccount,
+          bidVault: bidVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      // Verify buyer was refunded
+      const buyerBalanceAfter = await getAccount(
+        provider.connection,
+        takerPaymentAccount
+      );
+      assert.equal(
+        buyerBalanceAfter.amount.toString(),
+        buyerBalanceBefore.amount.toString(),
+        "Buyer should be fully refunded"
+      );
+      console.log("  ✅ Buyer refunded");
+
+      // Verify bid account is closed
       try {
-        await program.account.nftOffer.fetch(cancelOfferAccount);
-        assert.fail("Offer account should be closed");
+        await program.account.collectionBid.fetch(bidAccount);
+        assert.fail("Bid account should be closed");
       } catch (error) {
-        console.log("  ✅ Offer account closed successfully");
+        console.log("  ✅ Bid account closed successfully");
       }
 
-      console.log("\n✅ Cancel offer test passed!\n");
+      console.log("\n✅ Collection bid test passed!\n");
+    });
+
+    it("Should let a verified collection member fill a bid", async () => {
+      console.log("\n📝 Test: Accept Collection Bid");
+
+      const bidAmount = new anchor.BN(40 * 1_000_000); // 40 tokens
+      const collectionMint = await createMetadataNft(maker.publicKey);
+      const memberMint = await createMetadataNft(maker.publicKey, {
+        collection: collectionMint,
+      });
+      await verifyCollection(memberMint, collectionMint);
+      const nonMemberMint = await createMetadataNft(maker.publicKey);
+
+      const { makerProfile: buyerProfile, offer: bidAccount } = await nextOffer(
+        taker.publicKey,
+        "collection_bid"
+      );
+      const bidVault = await getAssociatedTokenAddress(
+        paymentMint,
+        bidAccount,
+        true // Allow PDA
+      );
+
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        takerPaymentAccount,
+        mintAuthority,
+        Number(bidAmount)
+      );
+      await program.methods
+        .makeBid(bidAmount)
+        .accounts({
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          buyer: taker.publicKey,
+          collectionMint: collectionMint,
+          paymentMint: paymentMint,
+          buyerPaymentAccount: takerPaymentAccount,
+          makerProfile: buyerProfile,
+          bid: bidAccount,
+          bidVault: bidVault,
+        })
+        .signers([taker])
+        .rpc();
+
+      const acceptAccounts = (nftMint: PublicKey) => ({
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        paymentTokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        seller: maker.publicKey,
+        buyer: taker.publicKey,
+        nftMint: nftMint,
+        nftMetadata: findMetadataPda(nftMint),
+        paymentMint: paymentMint,
+        sellerNftAccount: getAssociatedTokenAddressSync(
+          nftMint,
+          maker.publicKey
+        ),
+        buyerNftAccount: getAssociatedTokenAddressSync(
+          nftMint,
+          taker.publicKey
+        ),
+        sellerPaymentAccount: makerPaymentAccount,
+        config: configAccount,
+        treasury: treasury.publicKey,
+        treasuryPaymentAccount: treasuryPaymentAccount,
+        bid: bidAccount,
+        bidVault: bidVault,
+      });
+
+      // An NFT outside the collection cannot fill the bid
+      try {
+        await program.methods
+          .acceptBid()
+          .accounts(acceptAccounts(nonMemberMint))
+          .signers([maker])
+          .rpc();
+
+        assert.fail("Should have thrown error for a non-member NFT");
+      } catch (error) {
+        assert.include(
+          error.toString(),
+          "CollectionMismatch",
+          "Should fail with CollectionMismatch error"
+        );
+        console.log("  ✅ Correctly rejected an NFT outside the collection");
+      }
+
+      // Anyone can send a stray token to the public bid vault
+      await mintTo(
+        provider.connection,
+        taker,
+        paymentMint,
+        bidVault,
+        mintAuthority,
+        1
+      );
+
+      const sellerBalanceBefore = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      await program.methods
+        .acceptBid()
+        .accounts(acceptAccounts(memberMint))
+        .signers([maker])
+        .rpc();
+
+      // Buyer received the NFT and the seller the bid minus the fee
+      const buyerNftInfo = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(memberMint, taker.publicKey)
+      );
+      assert.equal(buyerNftInfo.amount.toString(), "1");
+      const sellerBalanceAfter = await getAccount(
+        provider.connection,
+        makerPaymentAccount
+      );
+      const bidFee = (Number(bidAmount) * feeBps) / 10_000;
+      assert.equal(
+        Number(sellerBalanceAfter.amount - sellerBalanceBefore.amount),
+        Number(bidAmount) - bidFee,
+        "Seller should receive the bid minus the fee"
+      );
+      const bidInfo = await provider.connection.getAccountInfo(bidAccount);
+      assert.isNull(bidInfo, "Bid account should be closed");
+      console.log("  ✅ Verified member filled the bid");
+
+      console.log("\n✅ Accept collection bid test passed!\n");
     });
   });
 
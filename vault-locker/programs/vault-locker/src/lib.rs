@@ -4,11 +4,11 @@ use anchor_lang::prelude::*;
 
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, TransferChecked},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
 pub mod states;
-use states::VaultState;
+use states::{VaultState, VestingSchedule};
 
 declare_id!("EZVS2aJZTU3AGQo6aesy3ogvHywdPu4hvUpgMaMDaSSM");
 
@@ -41,6 +41,30 @@ pub mod vault {
         ctx.accounts.unlock_vault()?;
         Ok(())
     }
+
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        ctx.accounts.reconcile()?;
+        msg!("Vault reconciled: {} tokens", ctx.accounts.state.amount);
+        Ok(())
+    }
+
+    pub fn lock_until(ctx: Context<Operations>, unlock_at: i64) -> Result<()> {
+        ctx.accounts.lock_until(unlock_at)?;
+        msg!("Vault locked until: {}", unlock_at);
+        Ok(())
+    }
+
+    pub fn grant_vesting(
+        ctx: Context<GrantVesting>,
+        amount: u64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+    ) -> Result<()> {
+        ctx.accounts.grant_vesting(amount, start, cliff, end, &ctx.bumps)?;
+        msg!("Vesting granted: {} tokens from {} to {}", amount, start, end);
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -53,13 +77,14 @@ pub struct Initialize<'info> {
         mut,
         associated_token::mint = vault_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
-    pub user_ata: Account<'info, TokenAccount>,
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
     // Vault state account
     #[account(
         init_if_needed,
         payer = user,
-        seeds = [b"state", user.key().as_ref()],
+        seeds = [b"state", user.key().as_ref(), vault_mint.key().as_ref()],
         bump,
         space = VaultState::INIT_SPACE
     )]
@@ -72,17 +97,19 @@ pub struct Initialize<'info> {
     )]
     pub vault: UncheckedAccount<'info>,
 
-    pub vault_mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub vault_mint: InterfaceAccount<'info, Mint>,
     // Vault's associated token account
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = vault_mint,
         associated_token::authority = vault,
+        associated_token::token_program = token_program,
     )]
-    pub vault_ata: Account<'info, TokenAccount>,
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
     // Programs
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -90,7 +117,18 @@ pub struct Initialize<'info> {
 impl<'info> Initialize<'info> {
     // Initialize the vault state
     pub fn initialize(&mut self, amount: u64, bumps: &InitializeBumps) -> Result<()> {
+        // Re-initializing an existing vault must not cut its time lock short
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.state.unlock_at, Errors::VaultTimeLocked);
+        // ...nor drop an outstanding vesting grant
+        require!(self.state.vesting.is_none(), Errors::VestingActive);
+        // The expected opening balance must match what the vault really holds
+        require!(amount == self.vault_ata.amount, Errors::BalanceMismatch);
         self.state.amount = amount;
+        self.state.deposited = amount;
+        self.state.withdrawn = 0;
+        self.state.unlock_at = 0;
+        self.state.vesting = None;
         self.state.state_bump = bumps.state;
         self.state.vault_bump = bumps.vault;
         self.state.is_initialized = false;
@@ -109,27 +147,30 @@ pub struct Operations<'info> {
         mut,
         associated_token::mint = vault_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
-    pub user_ata: Account<'info, TokenAccount>,
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer = user,
         space = VaultState::INIT_SPACE,
-        seeds = [b"state", user.key().as_ref()],
+        seeds = [b"state", user.key().as_ref(), vault_mint.key().as_ref()],
         bump,
     )]
     pub state: Account<'info, VaultState>,
 
-    pub vault_mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub vault_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
        init_if_needed,
        payer = user,
        associated_token::mint = vault_mint,
        associated_token::authority = vault,
+       associated_token::token_program = token_program,
     )]
-    pub vault_ata: Account<'info, TokenAccount>,
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: this is a PDA (no data stored here)
     #[account(
@@ -139,7 +180,7 @@ pub struct Operations<'info> {
     pub vault: UncheckedAccount<'info>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -147,6 +188,8 @@ impl<'info> Operations<'info> {
     // Deposit tokens into the vault
     pub fn deposit(&mut self, amount: u64) -> Result<()> {
         require!(!self.state.is_initialized, Errors::VaultInitialized);
+        require!(self.state.vesting.is_none(), Errors::VestingActive);
+        let balance_before = self.vault_ata.amount;
         // Transfer tokens from user to vault
         let cpi_program = self.token_program.to_account_info();
         // transfer checked to ensure correct decimals
@@ -159,13 +202,38 @@ impl<'info> Operations<'info> {
         // Create the CPI context
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         // Perform the token transfer
-        token::transfer_checked(cpi_ctx, amount, self.vault_mint.decimals)?;
-        Ok(())
+        token_interface::transfer_checked(cpi_ctx, amount, self.vault_mint.decimals)?;
+
+        // Transfer-fee mints deliver less than `amount`; record what actually arrived
+        self.vault_ata.reload()?;
+        let received = self
+            .vault_ata
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(Errors::BalanceMismatch)?;
+        self.state.record_deposit(received)?;
+        self.require_backed_balance()
     }
 
     // Withdraw tokens from the vault
     pub fn withdraw(&mut self, amount: u64) -> Result<()> {
         require!(!self.state.is_initialized, Errors::VaultInitialized);
+        self.require_unlock_time()?;
+        self.state.record_withdrawal(amount)?;
+
+        // A vesting vault only releases what has vested and not been claimed yet
+        if let Some(vesting) = self.state.vesting.as_mut() {
+            let now = Clock::get()?.unix_timestamp;
+            require!(
+                amount <= vesting.claimable_amount(now)?,
+                Errors::InsufficientVestedAmount
+            );
+            vesting.claimed = vesting.claimed.checked_add(amount).ok_or(Errors::MathOverflow)?;
+            // Fully claimed grants turn the vault back into a regular one
+            if vesting.claimed == vesting.total {
+                self.state.vesting = None;
+            }
+        }
 
         // seeds for PDA signer: ["vault", user_pubkey, vault_bump]
         let binding = self.user.key();
@@ -184,23 +252,207 @@ impl<'info> Operations<'info> {
             authority: self.vault.to_account_info(),
         };
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        token::transfer_checked(cpi_ctx, amount, self.vault_mint.decimals)?;
-        Ok(())
+        token_interface::transfer_checked(cpi_ctx, amount, self.vault_mint.decimals)?;
+        self.require_backed_balance()
+    }
+    // Fail if the vault holds fewer tokens than recorded after a transfer
+    fn require_backed_balance(&mut self) -> Result<()> {
+        self.vault_ata.reload()?;
+        self.state.require_backed_by(self.vault_ata.amount)
     }
     // Lock the vault
     pub fn lock_vault(&mut self) -> Result<()> {
         self.state.is_initialized = true;
         Ok(())
     }
-    // Unlock the vault
+    // Unlock the vault (only once any time lock has passed)
     pub fn unlock_vault(&mut self) -> Result<()> {
+        self.require_unlock_time()?;
         self.state.is_initialized = false;
         Ok(())
     }
+    // Lock the vault until a timestamp; an existing lock can only be extended
+    pub fn lock_until(&mut self, unlock_at: i64) -> Result<()> {
+        require!(unlock_at >= self.state.unlock_at, Errors::LockShortened);
+        self.state.unlock_at = unlock_at;
+        self.state.is_initialized = true;
+        Ok(())
+    }
+    // Fail while the Clock is still before the vault's unlock time
+    fn require_unlock_time(&self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.state.unlock_at, Errors::VaultTimeLocked);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+// This struct defines the read-only accounts required to check a vault's balance
+pub struct Reconcile<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"state", user.key().as_ref(), vault_mint.key().as_ref()],
+        bump,
+    )]
+    pub state: Account<'info, VaultState>,
+
+    #[account(mint::token_program = token_program)]
+    pub vault_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = vault_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: this is a PDA (no data stored here)
+    #[account(
+        seeds = [b"vault", user.key().as_ref()],
+        bump,
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Reconcile<'info> {
+    // Fail loudly if the vault holds fewer tokens than recorded
+    pub fn reconcile(&self) -> Result<()> {
+        self.state.require_backed_by(self.vault_ata.amount)
+    }
+}
+
+#[derive(Accounts)]
+// This struct defines the accounts required for a grantor to fund a beneficiary's vesting vault
+pub struct GrantVesting<'info> {
+    #[account(mut)]
+    pub grantor: Signer<'info>,
+    // Grantor's associated token account
+    #[account(
+        mut,
+        associated_token::mint = vault_mint,
+        associated_token::authority = grantor,
+        associated_token::token_program = token_program,
+    )]
+    pub grantor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // The beneficiary co-signs so nobody can force a grant onto their vault
+    pub beneficiary: Signer<'info>,
+    // Beneficiary's vault state account
+    #[account(
+        init_if_needed,
+        payer = grantor,
+        space = VaultState::INIT_SPACE,
+        seeds = [b"state", beneficiary.key().as_ref(), vault_mint.key().as_ref()],
+        bump,
+    )]
+    pub state: Account<'info, VaultState>,
+
+    /// CHECK: this is a PDA (no data stored here)
+    #[account(
+        seeds = [b"vault", beneficiary.key().as_ref()],
+        bump,
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub vault_mint: InterfaceAccount<'info, Mint>,
+    // Beneficiary vault's associated token account
+    #[account(
+        init_if_needed,
+        payer = grantor,
+        associated_token::mint = vault_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+    // Programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GrantVesting<'info> {
+    // Fund the beneficiary's vault and record the vesting schedule
+    pub fn grant_vesting(
+        &mut self,
+        amount: u64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+        bumps: &GrantVestingBumps,
+    ) -> Result<()> {
+        // Timestamps too far apart to subtract could never be vested against
+        require!(
+            amount > 0
+                && start <= cliff
+                && cliff <= end
+                && start < end
+                && end.checked_sub(start).is_some(),
+            Errors::InvalidVestingSchedule
+        );
+        // One grant per vault, and never mixed with the beneficiary's own tokens
+        // (tokens sent to the vault directly are not recorded and do not count)
+        require!(self.state.vesting.is_none(), Errors::VestingActive);
+        require!(self.state.amount == 0, Errors::VaultNotEmpty);
+        let balance_before = self.vault_ata.amount;
+
+        // Transfer the granted tokens from grantor to the beneficiary's vault
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.grantor_ata.to_account_info(),
+            to: self.vault_ata.to_account_info(),
+            mint: self.vault_mint.to_account_info(),
+            authority: self.grantor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, self.vault_mint.decimals)?;
+
+        // Transfer-fee mints deliver less than `amount`; record what actually arrived
+        self.vault_ata.reload()?;
+        let received = self
+            .vault_ata
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(Errors::BalanceMismatch)?;
+        self.state.record_deposit(received)?;
+
+        self.state.vesting = Some(VestingSchedule {
+            grantor: self.grantor.key(),
+            total: received,
+            claimed: 0,
+            start,
+            cliff,
+            end,
+        });
+        self.state.vault_bump = bumps.vault;
+        self.state.state_bump = bumps.state;
+        Ok(())
+    }
 }
 
 #[error_code]
 pub enum Errors {
     #[msg("The vault is initialized")]
     VaultInitialized,
+    #[msg("The vault is time-locked until its unlock time")]
+    VaultTimeLocked,
+    #[msg("A lock can be extended but never shortened")]
+    LockShortened,
+    #[msg("Vesting needs a non-zero amount and start <= cliff <= end")]
+    InvalidVestingSchedule,
+    #[msg("The vault holds an active vesting grant")]
+    VestingActive,
+    #[msg("The vault already holds the owner's tokens")]
+    VaultNotEmpty,
+    #[msg("Amount exceeds the vested tokens not yet claimed")]
+    InsufficientVestedAmount,
+    #[msg("Amount exceeds the tokens held in the vault")]
+    InsufficientVaultBalance,
+    #[msg("Recorded vault amount does not match the vault token balance")]
+    BalanceMismatch,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
 }
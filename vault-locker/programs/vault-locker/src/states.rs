@@ -1,15 +1,79 @@
 use anchor_lang::prelude::*;
 
+use crate::Errors;
+
 
 // Define the VaultState account structure
 #[account]
 pub struct VaultState {
     pub is_initialized: bool,
-    pub amount: u64, // Amount of tokens locked
+    pub amount: u64, // Amount of tokens currently held for the owner (deposited - withdrawn)
+    pub deposited: u64, // Total tokens ever deposited
+    pub withdrawn: u64, // Total tokens ever withdrawn
+    pub unlock_at: i64, // Unix timestamp before which the vault cannot be unlocked
+    pub vesting: Option<VestingSchedule>, // Grant vesting to the vault owner, if any
     pub vault_bump: u8, // Bump for PDA
     pub state_bump: u8, // Bump for state PDA
 }
 
 impl Space for VaultState {
-    const INIT_SPACE: usize = 8 + 1 + 8 + 1 + 1; // Discriminator + is_initialized + amount + vault_bump + state_bump
+    const INIT_SPACE: usize = 8 + 1 + 8 + 8 + 8 + 8 + (1 + VestingSchedule::INIT_SPACE) + 1 + 1; // Discriminator + is_initialized + amount + deposited + withdrawn + unlock_at + vesting + vault_bump + state_bump
+}
+
+impl VaultState {
+    // Record tokens that arrived in the vault
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.deposited = self.deposited.checked_add(amount).ok_or(Errors::MathOverflow)?;
+        self.amount = self.amount.checked_add(amount).ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+
+    // Fail if a vault balance does not cover the recorded amount
+    // (tokens sent to the vault directly are not recorded and are ignored)
+    pub fn require_backed_by(&self, balance: u64) -> Result<()> {
+        require!(balance >= self.amount, Errors::BalanceMismatch);
+        Ok(())
+    }
+
+    // Record tokens that left the vault (never more than it holds)
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.amount = self.amount.checked_sub(amount).ok_or(Errors::InsufficientVaultBalance)?;
+        self.withdrawn = self.withdrawn.checked_add(amount).ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+}
+
+// Define the linear vesting schedule of a grant
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingSchedule {
+    pub grantor: Pubkey, // Wallet that funded the grant
+    pub total: u64, // Tokens granted
+    pub claimed: u64, // Vested tokens already withdrawn
+    pub start: i64, // Unix timestamp vesting starts from
+    pub cliff: i64, // Unix timestamp before which nothing is vested
+    pub end: i64, // Unix timestamp when everything is vested
+}
+
+impl Space for VestingSchedule {
+    const INIT_SPACE: usize = 32 + 8 + 8 + 8 + 8 + 8; // grantor + total + claimed + start + cliff + end
+}
+
+impl VestingSchedule {
+    // Tokens vested at the given unix timestamp (linear from start, nothing before the cliff)
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if now < self.cliff {
+            return Ok(0);
+        }
+        if now >= self.end {
+            return Ok(self.total);
+        }
+        let elapsed = now.checked_sub(self.start).ok_or(Errors::MathOverflow)? as u128;
+        let duration = self.end.checked_sub(self.start).ok_or(Errors::MathOverflow)? as u128;
+        Ok((self.total as u128 * elapsed / duration) as u64)
+    }
+
+    // Vested tokens not yet withdrawn
+    pub fn claimable_amount(&self, now: i64) -> Result<u64> {
+        Ok(self.vested_amount(now)?.saturating_sub(self.claimed))
+    }
 }
@@ -1,12 +1,19 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Vault } from "../target/types/vault";
+import { assert } from "chai";
 import {
   createMint,
   getOrCreateAssociatedTokenAccount,
   mintTo,
   getAccount,
+  getAssociatedTokenAddress,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  ExtensionType,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 
@@ -58,7 +65,11 @@ describe("vault (devnet) — full flow", () => {
 
     // 4️⃣ Derive PDAs
     [statePda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("state"), user.publicKey.toBuffer()],
+      [
+        Buffer.from("state"),
+        user.publicKey.toBuffer(),
+        mintAccount.toBuffer(),
+      ],
       program.programId
     );
     [vaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -113,6 +124,44 @@ describe("vault (devnet) — full flow", () => {
       .rpc();
     console.log("Deposit tx:", tx);
 
+    // Recorded amounts must be backed by the real vault balance
+    const reconcileAccounts = {
+      user: user.publicKey,
+      state: statePda,
+      vaultMint: mintAccount,
+      vaultAta: vaultAta.address,
+      vault: vaultPda,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const txReconcile = await program.methods
+      .reconcile()
+      .accounts(reconcileAccounts)
+      .rpc();
+    console.log("Reconcile tx:", txReconcile);
+
+    // Tokens sent straight to the vault are ignored rather than failing reconcile
+    await mintTo(
+      provider.connection,
+      user.payer,
+      mintAccount,
+      vaultAta.address,
+      user.payer,
+      1
+    );
+    const txDustReconcile = await program.methods
+      .reconcile()
+      .accounts(reconcileAccounts)
+      .rpc();
+    console.log("✅ Reconcile with stray tokens in the vault:", txDustReconcile);
+
+    const state = await program.account.vaultState.fetch(statePda);
+    console.log(
+      "Recorded amount / deposited / withdrawn:",
+      state.amount.toString(),
+      state.deposited.toString(),
+      state.withdrawn.toString()
+    );
+
     await printBalances(provider.connection, userAta.address, vaultAta.address);
   });
 
@@ -193,6 +242,386 @@ describe("vault (devnet) — full flow", () => {
 
     await printBalances(provider.connection, userAta.address, vaultAta.address);
   });
+
+  it("time-locks the vault until unlock_at", async () => {
+    const unlockAt = Math.floor(Date.now() / 1000) + 10;
+    const txLock = await program.methods
+      .lockUntil(new anchor.BN(unlockAt))
+      .accounts({
+        user: user.publicKey,
+        userAta: userAta.address,
+        state: statePda,
+        vault: vaultPda,
+        vaultMint: mintAccount,
+        vaultAta: vaultAta.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log("Vault locked until", unlockAt, txLock);
+
+    // Unlocking before unlock_at must fail
+    try {
+      await program.methods
+        .unlock()
+        .accounts({
+          user: user.publicKey,
+          userAta: userAta.address,
+          state: statePda,
+          vault: vaultPda,
+          vaultMint: mintAccount,
+          vaultAta: vaultAta.address,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      console.error("❌ Unlock should have failed before unlock_at");
+    } catch (err: any) {
+      console.log("✅ Unlock failed before unlock_at (expected):", err.error?.errorMessage);
+    }
+
+    // Shortening the lock must fail
+    try {
+      await program.methods
+        .lockUntil(new anchor.BN(unlockAt - 5))
+        .accounts({
+          user: user.publicKey,
+          userAta: userAta.address,
+          state: statePda,
+          vault: vaultPda,
+          vaultMint: mintAccount,
+          vaultAta: vaultAta.address,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      console.error("❌ Shortening the lock should have failed");
+    } catch (err: any) {
+      console.log("✅ Shortening the lock failed (expected):", err.error?.errorMessage);
+    }
+
+    // Once the Clock passes unlock_at the vault unlocks normally
+    await new Promise((resolve) => setTimeout(resolve, 15_000));
+    const txUnlock = await program.methods
+      .unlock()
+      .accounts({
+        user: user.publicKey,
+        userAta: userAta.address,
+        state: statePda,
+        vault: vaultPda,
+        vaultMint: mintAccount,
+        vaultAta: vaultAta.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log("Vault unlocked after unlock_at:", txUnlock);
+  });
+
+  it("vests a grant linearly and limits withdrawals to vested tokens", async () => {
+    const beneficiary = anchor.web3.Keypair.generate();
+    const GRANT_AMOUNT = 100 * 10 ** DECIMALS;
+
+    // Fund the beneficiary so they can pay transaction fees
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: user.publicKey,
+          toPubkey: beneficiary.publicKey,
+          lamports: 0.01 * anchor.web3.LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    const [beneficiaryState] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("state"),
+        beneficiary.publicKey.toBuffer(),
+        mintAccount.toBuffer(),
+      ],
+      program.programId
+    );
+    const [beneficiaryVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), beneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+    const beneficiaryVaultAta = await getAssociatedTokenAddress(
+      mintAccount,
+      beneficiaryVault,
+      true
+    );
+    const beneficiaryAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      user.payer,
+      mintAccount,
+      beneficiary.publicKey
+    );
+
+    const grantAccounts = {
+      grantor: user.publicKey,
+      grantorAta: userAta.address,
+      beneficiary: beneficiary.publicKey,
+      state: beneficiaryState,
+      vault: beneficiaryVault,
+      vaultMint: mintAccount,
+      vaultAta: beneficiaryVaultAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const now = Math.floor(Date.now() / 1000);
+
+    // A schedule spanning the whole i64 range could never be vested against
+    try {
+      await program.methods
+        .grantVesting(
+          new anchor.BN(GRANT_AMOUNT),
+          new anchor.BN("-9223372036854775808"),
+          new anchor.BN(now),
+          new anchor.BN("9223372036854775807")
+        )
+        .accounts(grantAccounts)
+        .signers([beneficiary])
+        .rpc();
+      console.error("❌ Grant with extreme timestamps should have failed");
+    } catch (err: any) {
+      console.log("✅ Extreme vesting schedule rejected (expected):", err.error?.errorMessage);
+    }
+
+    // Halfway through the schedule, past the cliff
+    const txGrant = await program.methods
+      .grantVesting(
+        new anchor.BN(GRANT_AMOUNT),
+        new anchor.BN(now - 100),
+        new anchor.BN(now - 50),
+        new anchor.BN(now + 100)
+      )
+      .accounts(grantAccounts)
+      .signers([beneficiary])
+      .rpc();
+    console.log("Vesting granted:", txGrant);
+
+    const beneficiaryAccounts = {
+      user: beneficiary.publicKey,
+      userAta: beneficiaryAta.address,
+      state: beneficiaryState,
+      vault: beneficiaryVault,
+      vaultMint: mintAccount,
+      vaultAta: beneficiaryVaultAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // Withdrawing the whole grant must fail before it has fully vested
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(GRANT_AMOUNT))
+        .accounts(beneficiaryAccounts)
+        .signers([beneficiary])
+        .rpc();
+      console.error("❌ Withdrawing unvested tokens should have failed");
+    } catch (err: any) {
+      console.log("✅ Unvested withdrawal failed (expected):", err.error?.errorMessage);
+    }
+
+    // A quarter of the grant has certainly vested
+    const txWithdraw = await program.methods
+      .withdraw(new anchor.BN(GRANT_AMOUNT / 4))
+      .accounts(beneficiaryAccounts)
+      .signers([beneficiary])
+      .rpc();
+    console.log("Vested withdraw tx:", txWithdraw);
+
+    await printBalances(
+      provider.connection,
+      beneficiaryAta.address,
+      beneficiaryVaultAta
+    );
+  });
+
+  it("keeps an independent vault state per mint", async () => {
+    // A second token locked by the same user
+    const otherMint = await createMint(
+      provider.connection,
+      user.payer,
+      user.publicKey,
+      null,
+      DECIMALS
+    );
+    const otherUserAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      user.payer,
+      otherMint,
+      user.publicKey
+    );
+    const [otherStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("state"), user.publicKey.toBuffer(), otherMint.toBuffer()],
+      program.programId
+    );
+    const otherVaultAta = await getAssociatedTokenAddress(
+      otherMint,
+      vaultPda,
+      true
+    );
+
+    const otherAccounts = {
+      user: user.publicKey,
+      userAta: otherUserAta.address,
+      state: otherStatePda,
+      vault: vaultPda,
+      vaultMint: otherMint,
+      vaultAta: otherVaultAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    await program.methods
+      .initialize(new anchor.BN(0))
+      .accounts(otherAccounts)
+      .rpc();
+    const txLock = await program.methods.lock().accounts(otherAccounts).rpc();
+    console.log("Second mint locked:", txLock);
+
+    // Locking the second mint leaves the first mint's vault unlocked
+    const firstState = await program.account.vaultState.fetch(statePda);
+    const otherState = await program.account.vaultState.fetch(otherStatePda);
+    console.log(
+      "First mint locked / second mint locked:",
+      firstState.isInitialized,
+      otherState.isInitialized
+    );
+    assert.isFalse(firstState.isInitialized, "First mint should stay unlocked");
+    assert.isTrue(otherState.isInitialized, "Second mint should be locked");
+  });
+
+  it("records the amount actually received from a Token-2022 transfer-fee mint", async () => {
+    const FEE_BPS = 100; // 1% transfer fee
+
+    // 1️⃣ Create a Token-2022 mint with a transfer fee
+    const feeMint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const rent =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: user.publicKey,
+          newAccountPubkey: feeMint.publicKey,
+          space: mintLen,
+          lamports: rent,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          user.publicKey,
+          user.publicKey,
+          FEE_BPS,
+          BigInt(MINT_AMOUNT),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          feeMint.publicKey,
+          DECIMALS,
+          user.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [feeMint]
+    );
+
+    // 2️⃣ Mint tokens to the user's Token-2022 ATA
+    const feeUserAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      user.payer,
+      feeMint.publicKey,
+      user.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      user.payer,
+      feeMint.publicKey,
+      feeUserAta.address,
+      user.payer,
+      MINT_AMOUNT,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const [feeStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("state"),
+        user.publicKey.toBuffer(),
+        feeMint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const feeVaultAta = await getAssociatedTokenAddress(
+      feeMint.publicKey,
+      vaultPda,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const feeAccounts = {
+      user: user.publicKey,
+      userAta: feeUserAta.address,
+      state: feeStatePda,
+      vault: vaultPda,
+      vaultMint: feeMint.publicKey,
+      vaultAta: feeVaultAta,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // 3️⃣ Initialize and deposit; the vault receives the amount minus the fee
+    await program.methods
+      .initialize(new anchor.BN(0))
+      .accounts(feeAccounts)
+      .rpc();
+    const txDeposit = await program.methods
+      .deposit(new anchor.BN(DEPOSIT_AMOUNT))
+      .accounts(feeAccounts)
+      .rpc();
+    console.log("Transfer-fee deposit tx:", txDeposit);
+
+    const state = await program.account.vaultState.fetch(feeStatePda);
+    const expected = DEPOSIT_AMOUNT - (DEPOSIT_AMOUNT * FEE_BPS) / 10_000;
+    console.log(
+      "Recorded amount / expected after fee:",
+      state.amount.toString(),
+      expected
+    );
+    assert.equal(
+      state.amount.toNumber(),
+      expected,
+      "Recorded amount should exclude the transfer fee"
+    );
+
+    // 4️⃣ Recorded amount still reconciles with the real vault balance
+    const txReconcile = await program.methods
+      .reconcile()
+      .accounts({
+        user: user.publicKey,
+        state: feeStatePda,
+        vaultMint: feeMint.publicKey,
+        vaultAta: feeVaultAta,
+        vault: vaultPda,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+    console.log("Reconcile tx:", txReconcile);
+  });
 });
 
 // Helper — log balances for user + vault